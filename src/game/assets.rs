@@ -0,0 +1,197 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+#[cfg(feature = "hot-reload")]
+use std::time::{Duration, Instant};
+
+use glam::uvec2;
+use image::ImageError;
+
+use crate::renderer::{Atlas, AtlasId, AtlasRegionsError, RenderContext, Renderer};
+#[cfg(feature = "hot-reload")]
+use crate::trace_error;
+
+#[derive(Debug, Default)]
+pub struct Assets {
+    atlases: Vec<AtlasAsset>,
+    #[cfg(feature = "hot-reload")]
+    last_reload_check: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub enum NamedAtlasError {
+    Image(ImageError),
+    Regions(io::Error),
+    ParseRegions(AtlasRegionsError),
+}
+
+#[cfg_attr(
+    not(feature = "hot-reload"),
+    expect(dead_code, reason = "only read when reloading")
+)]
+#[derive(Debug)]
+struct AtlasAsset {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    atlas: AtlasId,
+}
+
+impl Assets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load_atlas(
+        &mut self,
+        path: impl AsRef<Path>,
+        renderer: &mut Renderer,
+        ctx: RenderContext,
+    ) -> Result<AtlasId, ImageError> {
+        let path = path.as_ref();
+        let modified = modified_time(path);
+        let image = image::open(path)?.to_rgba8();
+
+        let atlas = renderer.load_atlas(&image, ctx);
+
+        self.atlases.push(AtlasAsset {
+            path: path.to_path_buf(),
+            modified,
+            atlas,
+        });
+
+        Ok(atlas)
+    }
+
+    /// Reloads an atlas that was loaded without `Assets`, such as the renderer's default
+    /// atlas, from `path` when it changes.
+    pub fn watch_atlas(&mut self, path: impl AsRef<Path>, atlas: AtlasId) {
+        let path = path.as_ref();
+
+        self.atlases.push(AtlasAsset {
+            path: path.to_path_buf(),
+            modified: modified_time(path),
+            atlas,
+        });
+    }
+
+    /// Loads an atlas along with its named regions from a sidecar next to it with the `atlas`
+    /// extension, such as `sprites.atlas` for `sprites.png`. See `Atlas::parse_regions`.
+    #[expect(dead_code, reason = "the demo has no named atlases yet")]
+    pub fn load_named_atlas(
+        &mut self,
+        path: impl AsRef<Path>,
+        renderer: &mut Renderer,
+        ctx: RenderContext,
+    ) -> Result<Atlas, NamedAtlasError> {
+        let path = path.as_ref();
+        let regions =
+            fs::read_to_string(path.with_extension("atlas")).map_err(NamedAtlasError::Regions)?;
+
+        let (width, height) = image::image_dimensions(path).map_err(NamedAtlasError::Image)?;
+        let id = self
+            .load_atlas(path, renderer, ctx)
+            .map_err(NamedAtlasError::Image)?;
+
+        let mut atlas = Atlas::new(id, uvec2(width, height));
+        atlas
+            .parse_regions(&regions)
+            .map_err(NamedAtlasError::ParseRegions)?;
+
+        Ok(atlas)
+    }
+
+    /// Re-uploads the atlases whose files changed on disk. Meant to be called every frame, it
+    /// checks the files' modification times at most twice a second.
+    ///
+    /// Polling a handful of files costs a few `stat` calls per check, which avoids pulling in a
+    /// file watcher and its per-platform backends. Changes show up within half a second.
+    #[cfg(feature = "hot-reload")]
+    pub fn reload_changed(&mut self, renderer: &mut Renderer, ctx: RenderContext) {
+        const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+        let now = Instant::now();
+        if self
+            .last_reload_check
+            .is_some_and(|last_check| now - last_check < CHECK_INTERVAL)
+        {
+            return;
+        }
+
+        self.last_reload_check = Some(now);
+
+        for asset in self.take_changed() {
+            match image::open(&asset.path) {
+                Ok(image) => renderer.replace_atlas(asset.atlas, &image.to_rgba8(), ctx),
+                Err(err) => trace_error!("failed to reload {}: {err}", asset.path.display()),
+            }
+        }
+    }
+
+    /// The atlases whose files' modification times changed since they were loaded or last
+    /// taken.
+    #[cfg(feature = "hot-reload")]
+    fn take_changed(&mut self) -> impl Iterator<Item = &AtlasAsset> {
+        self.atlases.iter_mut().filter_map(|asset| {
+            let modified = modified_time(&asset.path);
+            if modified == asset.modified {
+                return None;
+            }
+
+            asset.modified = modified;
+
+            Some(&*asset)
+        })
+    }
+}
+
+impl Display for NamedAtlasError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Image(err) => write!(f, "failed to load atlas image: {err}"),
+            Self::Regions(err) => write!(f, "failed to read atlas regions: {err}"),
+            Self::ParseRegions(err) => write!(f, "failed to parse atlas regions: {err}"),
+        }
+    }
+}
+
+impl Error for NamedAtlasError {}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(all(test, feature = "hot-reload"))]
+mod tests {
+    use std::{env, fs::File, process};
+
+    use super::*;
+
+    #[test]
+    fn changed_modification_time_reloads_the_atlas() {
+        let path = env::temp_dir().join(format!("drill-assets-{}.png", process::id()));
+        let file = File::create(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+
+        let mut assets = Assets::new();
+        assets.watch_atlas(&path, AtlasId::default());
+        assert_eq!(assets.take_changed().count(), 0);
+
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+            .unwrap();
+        let changed = assets
+            .take_changed()
+            .map(|asset| asset.atlas)
+            .collect::<Vec<_>>();
+        assert_eq!(changed, [AtlasId::default()]);
+        assert_eq!(assets.take_changed().count(), 0);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
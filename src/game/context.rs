@@ -1,19 +1,42 @@
-use std::sync::{Arc, atomic::AtomicBool};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    sync::{
+        Arc,
+        mpsc::{self, Receiver},
+    },
+    time::{Duration, Instant},
+};
 
-use gilrs::Gilrs;
+use gilrs::{
+    GamepadId, Gilrs,
+    ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks},
+};
+use glam::{Vec2, vec2};
 use wgpu::{
-    Device, DeviceDescriptor, PollType, Queue, RequestAdapterOptions, Surface,
-    SurfaceConfiguration, TextureFormat, TextureViewDescriptor,
+    CreateSurfaceError, Device, DeviceLostReason, Features, Instance, Limits, PollType,
+    PresentMode, Queue, RequestAdapterError, RequestDeviceError, Surface, SurfaceConfiguration,
+    SurfaceError, TextureFormat, TextureViewDescriptor,
 };
 use winit::{
     application::ApplicationHandler,
-    event::{KeyEvent, WindowEvent},
+    dpi::PhysicalSize,
+    error::{EventLoopError, OsError},
+    event::{
+        DeviceEvent, DeviceId, ElementState, Ime, KeyEvent, MouseButton, MouseScrollDelta,
+        WindowEvent,
+    },
     event_loop::{ActiveEventLoop, EventLoop},
-    keyboard::{KeyCode, PhysicalKey},
-    window::{Window, WindowId},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
+    window::{CursorGrabMode, Window, WindowId},
 };
 
-use crate::game::Game;
+use crate::{
+    game::{Game, GamepadInfo},
+    trace_error, trace_span, trace_warn,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct GameContext<'a> {
@@ -21,14 +44,109 @@ pub struct GameContext<'a> {
     pub device: &'a Device,
     pub queue: &'a Queue,
     pub surface_format: TextureFormat,
-    should_exit: Option<&'a AtomicBool>,
+    #[expect(dead_code, reason = "part of the context API, unread by the demo")]
+    pub scale_factor: f64,
+    #[expect(dead_code, reason = "part of the context API, unread by the demo")]
+    pub modifiers: ModifiersState,
+    pub delta: Duration,
+    #[expect(dead_code, reason = "part of the context API, unread by the demo")]
+    pub elapsed: Duration,
+    flow: Option<&'a Cell<GameFlow>>,
+    rumble_requests: &'a RefCell<Vec<RumbleRequest>>,
+    present_mode_request: &'a Cell<Option<PresentMode>>,
+    gilrs: &'a Gilrs,
+}
+
+#[expect(dead_code, reason = "the demo never grabs the cursor")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorGrab {
+    #[default]
+    None,
+    /// Keeps the cursor inside the window.
+    Confined,
+    /// Keeps the cursor in place, for mouse look with `GameEvent::MouseMotion`.
+    ///
+    /// Not every platform supports locking. Where it doesn't, the cursor is confined instead.
+    Locked,
 }
 
+/// What the runner does after the current callback returns.
+///
+/// On `Exit` the runner calls `Game::end` and stops the event loop. On `Restart` it calls
+/// `Game::end`, drops the game and constructs a new one with `Game::new`, keeping the window,
+/// device, queue and surface. State that must survive a restart is the game's to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GameFlow {
+    #[default]
+    Continue,
+    Exit,
+    Restart,
+}
+
+#[expect(
+    dead_code,
+    reason = "games read the event data they need, and the demo needs little of it"
+)]
 #[derive(Debug)]
 pub enum GameEvent {
     CloseRequested,
-    Key { code: KeyCode, is_held: bool },
-    Button { code: ButtonCode, value: f32 },
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    Key {
+        code: KeyCode,
+        is_held: bool,
+        /// Set on the presses the OS repeats while a key is held down.
+        is_repeat: bool,
+    },
+    Button {
+        gamepad: GamepadId,
+        code: ButtonCode,
+        value: f32,
+    },
+    GamepadConnected(GamepadId),
+    GamepadDisconnected(GamepadId),
+    MouseMove {
+        position: Vec2,
+    },
+    MouseButton {
+        button: MouseButton,
+        is_held: bool,
+    },
+    Scroll {
+        delta: Vec2,
+    },
+    /// Raw relative mouse movement, reported even when the cursor is grabbed or at the edge of
+    /// the screen. Use it for mouse look rather than differences of `MouseMove` positions.
+    MouseMotion {
+        delta: Vec2,
+    },
+    /// Typed text with the keyboard layout and modifiers applied, for text fields rather than
+    /// controls. Also sent for committed IME input.
+    ///
+    /// Editing keys arrive as control characters, so handle them through `Key` instead:
+    ///
+    /// ```ignore
+    /// match event {
+    ///     GameEvent::Text(text) => name.extend(text.chars().filter(|c| !c.is_control())),
+    ///     GameEvent::Key { code: KeyCode::Backspace, is_held: true, .. } => _ = name.pop(),
+    ///     GameEvent::Key { code: KeyCode::Enter, is_held: true, .. } => submit(&name),
+    ///     _ => {}
+    /// }
+    /// ```
+    Text(String),
+    Modifiers(ModifiersState),
+    Focused(bool),
+    ScaleFactorChanged(f64),
+    /// The GPU device stopped working and every GPU resource made from it is unusable.
+    ///
+    /// The runner keeps the lost device, so a restart does not recover. Games should save what
+    /// they can and exit.
+    DeviceLost {
+        reason: DeviceLostReason,
+        message: String,
+    },
 }
 
 #[repr(u8)]
@@ -64,183 +182,512 @@ pub enum ButtonCode {
     Unknown,
 }
 
-pub fn run() {
-    let event_loop = EventLoop::new().expect("failed to create event loop");
+impl ButtonCode {
+    /// Whether this button reports continuous values in `0.0..=1.0` rather than only `0.0` or `1.0`.
+    ///
+    /// Analog buttons are best read through [`Value`](crate::input::Value), since
+    /// [`Button`](crate::input::Button) only reports whether they are past an activation threshold.
+    #[expect(dead_code, reason = "the demo binds no analog buttons")]
+    pub fn is_analog(self) -> bool {
+        matches!(
+            self,
+            Self::LeftStickRight
+                | Self::LeftStickLeft
+                | Self::LeftStickUp
+                | Self::LeftStickDown
+                | Self::RightStickRight
+                | Self::RightStickLeft
+                | Self::RightStickUp
+                | Self::RightStickDown
+                | Self::LeftTrigger2
+                | Self::RightTrigger2
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    EventLoop(EventLoopError),
+    Window(OsError),
+    Adapter(RequestAdapterError),
+    Device(RequestDeviceError),
+    Surface(CreateSurfaceError),
+    UnsupportedSurface,
+    Gilrs(Box<gilrs::Error>),
+    SurfaceTexture(SurfaceError),
+    Game(Box<dyn Error>),
+}
+
+pub fn run() -> Result<(), RunError> {
+    let event_loop = EventLoop::new().map_err(RunError::EventLoop)?;
     let mut runner = Runner::Uninit;
-    event_loop.run_app(&mut runner).expect("failed to run app");
+    event_loop
+        .run_app(&mut runner)
+        .map_err(RunError::EventLoop)?;
+
+    match runner {
+        Runner::Failed(err) => Err(err),
+        _ => Ok(()),
+    }
 }
 
 enum Runner {
     Uninit,
-    Init(InitRunner),
+    Init(Box<InitRunner>),
+    Failed(RunError),
 }
 
 struct InitRunner {
     window: Arc<Window>,
+    instance: Instance,
     device: Device,
     queue: Queue,
-    surface: Surface<'static>,
+    surface: Option<Surface<'static>>,
     surface_config: SurfaceConfiguration,
     gilrs: Gilrs,
-    game: Game,
+    rumble_requests: RefCell<Vec<RumbleRequest>>,
+    present_mode_request: Cell<Option<PresentMode>>,
+    present_modes: Vec<PresentMode>,
+    rumble_effects: HashMap<GamepadId, Effect>,
+    dpad_held: HashMap<(GamepadId, ButtonCode), bool>,
+    device_lost: Receiver<(DeviceLostReason, String)>,
+    game: RefCell<Game>,
+    modifiers: ModifiersState,
+    start_time: Option<Instant>,
+    next_frame: Option<Instant>,
+    delta: Duration,
+    elapsed: Duration,
+    is_minimized: bool,
+    has_ended: bool,
+    fatal_error: Option<RunError>,
+    fixed_update_lag: Duration,
+}
+
+#[derive(Debug)]
+struct RumbleRequest {
+    gamepad: GamepadId,
+    strength: f32,
+    duration: Duration,
 }
 
 impl<'a> GameContext<'a> {
     pub fn exit(&self) {
-        if let Some(should_exit) = self.should_exit {
-            should_exit.store(true, std::sync::atomic::Ordering::Relaxed);
+        self.set_flow(GameFlow::Exit);
+    }
+
+    #[expect(dead_code, reason = "the demo never restarts")]
+    pub fn restart(&self) {
+        self.set_flow(GameFlow::Restart);
+    }
+
+    fn set_flow(&self, flow: GameFlow) {
+        if let Some(cell) = self.flow {
+            cell.set(flow);
         } else {
-            panic!("cannot exit the game from this context");
+            panic!("cannot change the game flow from this context");
+        }
+    }
+
+    #[expect(dead_code, reason = "the demo has no rumble")]
+    pub fn set_rumble(&self, gamepad: GamepadId, strength: f32, duration: Duration) {
+        self.rumble_requests.borrow_mut().push(RumbleRequest {
+            gamepad,
+            strength,
+            duration,
+        });
+    }
+
+    /// The connected gamepads, empty when there are none.
+    #[expect(dead_code, reason = "the demo shows no button prompts")]
+    pub fn gamepads(&self) -> impl Iterator<Item = GamepadInfo> + 'a {
+        self.gilrs
+            .gamepads()
+            .map(|(id, gamepad)| GamepadInfo::new(id, gamepad))
+    }
+
+    #[expect(dead_code, reason = "the demo never grabs the cursor")]
+    pub fn set_cursor_grab(&self, grab: CursorGrab) {
+        let result = match grab {
+            CursorGrab::None => self.window.set_cursor_grab(CursorGrabMode::None),
+            CursorGrab::Confined => self.window.set_cursor_grab(CursorGrabMode::Confined),
+            CursorGrab::Locked => self
+                .window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| self.window.set_cursor_grab(CursorGrabMode::Confined)),
+        };
+
+        if let Err(err) = result {
+            trace_error!("failed to set cursor grab to {grab:?}: {err}");
+        }
+    }
+
+    #[expect(dead_code, reason = "the demo keeps the cursor visible")]
+    pub fn set_cursor_visible(&self, is_visible: bool) {
+        self.window.set_cursor_visible(is_visible);
+    }
+
+    /// Switches the present mode before the next frame, for example to `Immediate` for
+    /// unlimited FPS. Modes the surface doesn't support are ignored with a log.
+    #[expect(dead_code, reason = "the demo keeps Game::PRESENT_MODE")]
+    pub fn set_present_mode(&self, present_mode: PresentMode) {
+        self.present_mode_request.set(Some(present_mode));
+    }
+
+    /// The limits the device was created with, from `Game::device_descriptor`.
+    #[expect(dead_code, reason = "the demo has no optional GPU features")]
+    pub fn device_limits(&self) -> Limits {
+        self.device.limits()
+    }
+
+    /// The features enabled on the device, for skipping options such as wireframe debug
+    /// drawing when `POLYGON_MODE_LINE` is missing.
+    #[expect(dead_code, reason = "the demo has no optional GPU features")]
+    pub fn device_features(&self) -> Features {
+        self.device.features()
+    }
+}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EventLoop(err) => write!(f, "failed to run event loop: {err}"),
+            Self::Window(err) => write!(f, "failed to create window: {err}"),
+            Self::Adapter(err) => write!(f, "failed to get adapter: {err}"),
+            Self::Device(err) => write!(f, "failed to get device: {err}"),
+            Self::Surface(err) => write!(f, "failed to create surface: {err}"),
+            Self::UnsupportedSurface => write!(f, "surface is not supported by the adapter"),
+            Self::Gilrs(err) => write!(f, "failed to create gilrs: {err}"),
+            Self::SurfaceTexture(err) => write!(f, "failed to get surface texture: {err}"),
+            Self::Game(err) => write!(f, "failed to create game: {err}"),
+        }
+    }
+}
+
+impl Error for RunError {}
+
+impl Runner {
+    fn take_fatal_error(&mut self) {
+        if let Runner::Init(runner) = self
+            && let Some(err) = runner.fatal_error.take()
+        {
+            *self = Runner::Failed(err);
         }
     }
 }
 
 impl ApplicationHandler for Runner {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        if matches!(self, Runner::Uninit) {
-            *self = Runner::Init(InitRunner::new(event_loop));
+        match self {
+            Runner::Uninit => match InitRunner::new(event_loop) {
+                Ok(runner) => *self = Runner::Init(Box::new(runner)),
+                Err(err) => {
+                    *self = Runner::Failed(err);
+                    event_loop.exit();
+                }
+            },
+            Runner::Init(runner) => runner.resume(),
+            Runner::Failed(_) => {}
+        }
+    }
+
+    fn suspended(&mut self, _: &ActiveEventLoop) {
+        if let Runner::Init(runner) = self {
+            runner.suspend();
         }
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
         if let Runner::Init(runner) = self {
             runner.window_event(event_loop, event);
+            self.take_fatal_error();
+        }
+    }
+
+    fn device_event(&mut self, event_loop: &ActiveEventLoop, _: DeviceId, event: DeviceEvent) {
+        if let Runner::Init(runner) = self {
+            runner.device_event(event_loop, event);
+            self.take_fatal_error();
         }
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         if let Runner::Init(runner) = self {
             runner.about_to_wait(event_loop);
+            self.take_fatal_error();
+        }
+    }
+
+    fn exiting(&mut self, _: &ActiveEventLoop) {
+        if let Runner::Init(runner) = self {
+            runner.end();
         }
     }
 }
 
 impl InitRunner {
-    fn new(event_loop: &ActiveEventLoop) -> Self {
+    /// Borrows the whole runner, which is why the game sits in a `RefCell`.
+    fn context<'a>(&'a self, flow: Option<&'a Cell<GameFlow>>) -> GameContext<'a> {
+        GameContext {
+            window: &self.window,
+            device: &self.device,
+            queue: &self.queue,
+            surface_format: self.surface_config.format,
+            scale_factor: self.window.scale_factor(),
+            modifiers: self.modifiers,
+            delta: self.delta,
+            elapsed: self.elapsed,
+            flow,
+            rumble_requests: &self.rumble_requests,
+            present_mode_request: &self.present_mode_request,
+            gilrs: &self.gilrs,
+        }
+    }
+
+    fn new(event_loop: &ActiveEventLoop) -> Result<Self, RunError> {
         let window = {
             let window = event_loop
                 .create_window(Game::window_attributes())
-                .expect("failed to create window");
+                .map_err(RunError::Window)?;
 
             Arc::new(window)
         };
 
+        let instance = Instance::default();
+
         let device;
         let queue;
         let surface;
         let surface_config;
+        let present_modes;
         {
-            let instance = wgpu::Instance::default();
-
-            let adapter = instance.request_adapter(&RequestAdapterOptions::default());
-            let adapter = pollster::block_on(adapter).expect("failed to get adapter");
+            let adapter = instance.request_adapter(&Game::adapter_options());
+            let adapter = pollster::block_on(adapter).map_err(RunError::Adapter)?;
 
-            let device_queue = adapter.request_device(&DeviceDescriptor::default());
-            let device_queue = pollster::block_on(device_queue).expect("failed to get device");
+            let device_queue = adapter.request_device(&Game::device_descriptor());
+            let device_queue = pollster::block_on(device_queue).map_err(RunError::Device)?;
             device = device_queue.0;
             queue = device_queue.1;
 
             surface = instance
                 .create_surface(window.clone())
-                .expect("failed to create surface");
+                .map_err(RunError::Surface)?;
 
-            surface_config = surface
+            let mut default_config = surface
                 .get_default_config(
                     &adapter,
                     window.inner_size().width,
                     window.inner_size().height,
                 )
-                .expect("failed to create surface config");
+                .ok_or(RunError::UnsupportedSurface)?;
+
+            present_modes = surface.get_capabilities(&adapter).present_modes;
+            if matches!(
+                Game::PRESENT_MODE,
+                PresentMode::AutoVsync | PresentMode::AutoNoVsync
+            ) || present_modes.contains(&Game::PRESENT_MODE)
+            {
+                default_config.present_mode = Game::PRESENT_MODE;
+            } else {
+                trace_warn!(
+                    "present mode {:?} is not supported, falling back to {:?}",
+                    Game::PRESENT_MODE,
+                    default_config.present_mode
+                );
+            }
+
+            surface_config = default_config;
 
             surface.configure(&device, &surface_config);
         };
 
-        let gilrs = Gilrs::new().expect("failed to create gilrs");
+        let (device_lost_sender, device_lost) = mpsc::channel();
+        device.set_device_lost_callback(move |reason, message| {
+            let _ = device_lost_sender.send((reason, message));
+        });
+
+        let gilrs = Gilrs::new().map_err(|err| RunError::Gilrs(Box::new(err)))?;
+
+        let rumble_requests = RefCell::new(Vec::new());
+        let present_mode_request = Cell::new(None);
 
         let game = Game::new(GameContext {
             window: &window,
             device: &device,
             queue: &queue,
             surface_format: surface_config.format,
-            should_exit: None,
-        });
+            scale_factor: window.scale_factor(),
+            modifiers: ModifiersState::empty(),
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            flow: None,
+            rumble_requests: &rumble_requests,
+            present_mode_request: &present_mode_request,
+            gilrs: &gilrs,
+        })
+        .map_err(RunError::Game)?;
 
-        Self {
+        Ok(Self {
             window,
+            instance,
             device,
             queue,
-            surface,
+            surface: Some(surface),
             surface_config,
             gilrs,
-            game,
-        }
+            rumble_requests,
+            present_mode_request,
+            present_modes,
+            rumble_effects: HashMap::new(),
+            dpad_held: HashMap::new(),
+            device_lost,
+            game: RefCell::new(game),
+            modifiers: ModifiersState::empty(),
+            start_time: None,
+            next_frame: None,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            is_minimized: false,
+            has_ended: false,
+            fatal_error: None,
+            fixed_update_lag: Duration::ZERO,
+        })
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, event: WindowEvent) {
+        if self.has_ended {
+            return;
+        }
+
         let game_event = match &event {
             WindowEvent::CloseRequested => Some(GameEvent::CloseRequested),
 
+            WindowEvent::Resized(new_size) if new_size.width > 0 && new_size.height > 0 => {
+                Some(GameEvent::Resized {
+                    width: new_size.width,
+                    height: new_size.height,
+                })
+            }
+
             WindowEvent::KeyboardInput {
                 event:
                     KeyEvent {
                         physical_key: PhysicalKey::Code(code),
                         state,
+                        repeat,
                         ..
                     },
                 ..
             } => Some(GameEvent::Key {
                 code: *code,
                 is_held: state.is_pressed(),
+                is_repeat: *repeat,
             }),
 
+            WindowEvent::CursorMoved { position, .. } => Some(GameEvent::MouseMove {
+                position: vec2(position.x as f32, position.y as f32),
+            }),
+
+            WindowEvent::MouseInput { state, button, .. } => Some(GameEvent::MouseButton {
+                button: *button,
+                is_held: state.is_pressed(),
+            }),
+
+            WindowEvent::MouseWheel { delta, .. } => Some(GameEvent::Scroll {
+                delta: match delta {
+                    MouseScrollDelta::LineDelta(x, y) => vec2(*x, *y),
+                    MouseScrollDelta::PixelDelta(delta) => vec2(delta.x as f32, delta.y as f32),
+                },
+            }),
+
+            WindowEvent::Focused(is_focused) => Some(GameEvent::Focused(*is_focused)),
+
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                Some(GameEvent::ScaleFactorChanged(*scale_factor))
+            }
+
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+
+                Some(GameEvent::Modifiers(self.modifiers))
+            }
+
             _ => None,
         };
 
-        if let Some(game_event) = &game_event {
-            let should_exit = AtomicBool::new(false);
-
-            self.game.event(
-                game_event,
-                GameContext {
-                    window: &self.window,
-                    device: &self.device,
-                    queue: &self.queue,
-                    surface_format: self.surface_config.format,
-                    should_exit: Some(&should_exit),
-                },
-            );
+        let text_event = match &event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        text: Some(text),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => Some(GameEvent::Text(text.to_string())),
 
-            if should_exit.load(std::sync::atomic::Ordering::Relaxed) {
-                self.game.end(GameContext {
-                    window: &self.window,
-                    device: &self.device,
-                    queue: &self.queue,
-                    surface_format: self.surface_config.format,
-                    should_exit: None,
-                });
+            WindowEvent::Ime(Ime::Commit(text)) => Some(GameEvent::Text(text.clone())),
 
-                event_loop.exit();
+            _ => None,
+        };
+
+        for game_event in [game_event, text_event].iter().flatten() {
+            trace_span!("event");
+
+            let flow = Cell::new(GameFlow::Continue);
+
+            self.game
+                .borrow_mut()
+                .event(game_event, self.context(Some(&flow)));
+
+            if self.apply_flow(flow.get(), event_loop) {
                 return;
             }
+
+            if flow.get() != GameFlow::Continue {
+                break;
+            }
         }
 
         match &event {
-            WindowEvent::RedrawRequested => {
-                let Ok(surface_texture) = self.surface.get_current_texture() else {
+            WindowEvent::RedrawRequested if !self.is_minimized => {
+                trace_span!("redraw");
+
+                let Some(surface) = &self.surface else {
                     return;
                 };
 
-                self.game.render(
+                let surface_texture = match surface.get_current_texture() {
+                    Ok(surface_texture) => surface_texture,
+                    Err(err @ (SurfaceError::Lost | SurfaceError::Outdated)) => {
+                        trace_warn!("surface is {err:?}, reconfiguring it");
+
+                        surface.configure(&self.device, &self.surface_config);
+
+                        match surface.get_current_texture() {
+                            Ok(surface_texture) => surface_texture,
+                            Err(err) => {
+                                trace_error!(
+                                    "failed to get surface texture after reconfiguring: {err}"
+                                );
+                                return;
+                            }
+                        }
+                    }
+                    Err(SurfaceError::OutOfMemory) => {
+                        self.fatal_error =
+                            Some(RunError::SurfaceTexture(SurfaceError::OutOfMemory));
+                        self.end();
+                        event_loop.exit();
+                        return;
+                    }
+                    Err(SurfaceError::Timeout | SurfaceError::Other) => return,
+                };
+
+                self.game.borrow_mut().render(
                     &surface_texture
                         .texture
                         .create_view(&TextureViewDescriptor::default()),
-                    GameContext {
-                        window: &self.window,
-                        device: &self.device,
-                        queue: &self.queue,
-                        surface_format: self.surface_config.format,
-                        should_exit: None,
-                    },
+                    self.context(None),
                 );
 
                 self.window.pre_present_notify();
@@ -251,57 +698,213 @@ impl InitRunner {
                     .expect("failed to poll device");
             }
 
-            WindowEvent::Resized(new_size) => {
-                self.surface_config.width = new_size.width;
-                self.surface_config.height = new_size.height;
-                self.surface.configure(&self.device, &self.surface_config);
+            event => {
+                if let Some(new_size) = surface_resize(event) {
+                    self.resize_surface(new_size);
+                }
             }
+        }
+    }
 
-            _ => {}
+    fn device_event(&mut self, event_loop: &ActiveEventLoop, event: DeviceEvent) {
+        if self.has_ended {
+            return;
         }
+
+        let game_event = match event {
+            DeviceEvent::MouseMotion { delta } => GameEvent::MouseMotion {
+                delta: vec2(delta.0 as f32, delta.1 as f32),
+            },
+
+            _ => return,
+        };
+
+        let flow = Cell::new(GameFlow::Continue);
+
+        self.game
+            .borrow_mut()
+            .event(&game_event, self.context(Some(&flow)));
+
+        self.apply_flow(flow.get(), event_loop);
+    }
+
+    fn resize_surface(&mut self, new_size: PhysicalSize<u32>) {
+        self.is_minimized = !resize_surface_config(&mut self.surface_config, new_size);
+
+        if !self.is_minimized
+            && let Some(surface) = &self.surface
+        {
+            surface.configure(&self.device, &self.surface_config);
+        }
+    }
+
+    /// Drops the surface, which some platforms invalidate while the app is in the background.
+    fn suspend(&mut self) {
+        if self.has_ended || self.surface.is_none() {
+            return;
+        }
+
+        self.game.borrow_mut().suspend(self.context(None));
+
+        self.surface = None;
+    }
+
+    fn resume(&mut self) {
+        if self.has_ended || self.surface.is_some() {
+            return;
+        }
+
+        let surface = match self.instance.create_surface(self.window.clone()) {
+            Ok(surface) => surface,
+            Err(err) => {
+                trace_error!("failed to recreate surface: {err}");
+                return;
+            }
+        };
+
+        self.surface = Some(surface);
+        self.resize_surface(self.window.inner_size());
+
+        self.game.borrow_mut().resume(self.context(None));
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        self.handle_gilrs_events(event_loop);
+        if self.has_ended {
+            return;
+        }
 
-        let should_exit = AtomicBool::new(false);
+        if let Some(target_fps) = Game::TARGET_FPS
+            && target_fps > 0
+        {
+            let frame_time = Duration::from_secs(1) / target_fps;
+            let now = Instant::now();
 
-        self.game.update(GameContext {
-            window: &self.window,
-            device: &self.device,
-            queue: &self.queue,
-            surface_format: self.surface_config.format,
-            should_exit: Some(&should_exit),
-        });
+            match self.next_frame {
+                Some(next_frame) if now < next_frame => {
+                    std::thread::sleep(next_frame - now);
+                    self.next_frame = Some(next_frame + frame_time);
+                }
+                _ => self.next_frame = Some(now + frame_time),
+            }
+        }
 
-        if should_exit.load(std::sync::atomic::Ordering::Relaxed) {
-            self.game.end(GameContext {
-                window: &self.window,
-                device: &self.device,
-                queue: &self.queue,
-                surface_format: self.surface_config.format,
-                should_exit: None,
-            });
+        let now = Instant::now();
+        let elapsed = now - *self.start_time.get_or_insert(now);
+        self.delta = elapsed - self.elapsed;
+        self.elapsed = elapsed;
 
-            event_loop.exit();
+        if self.handle_gilrs_events(event_loop) || self.handle_device_lost(event_loop) {
             return;
         }
 
-        self.window.request_redraw();
+        let flow = Cell::new(GameFlow::Continue);
+
+        if let Some(fixed_timestep) = Game::FIXED_TIMESTEP {
+            // Dropping the lag past the cap slows the game down instead of running ever more
+            // fixed updates to catch up after a long frame, such as one after a suspend.
+            self.fixed_update_lag = (self.fixed_update_lag + self.delta)
+                .min(fixed_timestep * Game::MAX_FIXED_UPDATES_PER_FRAME);
+
+            while self.fixed_update_lag >= fixed_timestep && flow.get() == GameFlow::Continue {
+                trace_span!("fixed_update");
+
+                self.fixed_update_lag -= fixed_timestep;
+
+                self.game.borrow_mut().fixed_update(GameContext {
+                    delta: fixed_timestep,
+                    ..self.context(Some(&flow))
+                });
+            }
+        }
+
+        if flow.get() == GameFlow::Continue {
+            trace_span!("update");
+
+            self.game.borrow_mut().update(self.context(Some(&flow)));
+        }
+
+        if self.apply_flow(flow.get(), event_loop) {
+            return;
+        }
+
+        self.handle_rumble_requests();
+        self.handle_present_mode_request();
+
+        if !self.is_minimized {
+            self.window.request_redraw();
+        }
     }
 
-    fn handle_gilrs_events(&mut self, event_loop: &ActiveEventLoop) {
-        let should_exit = AtomicBool::new(false);
+    fn handle_present_mode_request(&mut self) {
+        let Some(present_mode) = self.present_mode_request.take() else {
+            return;
+        };
+
+        if present_mode == self.surface_config.present_mode {
+            return;
+        }
+
+        if !matches!(
+            present_mode,
+            PresentMode::AutoVsync | PresentMode::AutoNoVsync
+        ) && !self.present_modes.contains(&present_mode)
+        {
+            trace_warn!("present mode {present_mode:?} is not supported, ignoring it");
+            return;
+        }
 
-        while let Some(event) = self.gilrs.next_event() {
-            let ctx = GameContext {
-                window: &self.window,
-                device: &self.device,
-                queue: &self.queue,
-                surface_format: self.surface_config.format,
-                should_exit: Some(&should_exit),
+        self.surface_config.present_mode = present_mode;
+
+        if let Some(surface) = &self.surface
+            && !self.is_minimized
+        {
+            surface.configure(&self.device, &self.surface_config);
+        }
+    }
+
+    fn handle_rumble_requests(&mut self) {
+        for request in self.rumble_requests.get_mut().drain(..) {
+            let Some(gamepad) = self.gilrs.connected_gamepad(request.gamepad) else {
+                continue;
             };
 
+            if !gamepad.is_ff_supported() {
+                continue;
+            }
+
+            let duration = Ticks::from_ms(request.duration.as_millis() as u32);
+            let magnitude = (request.strength.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+
+            let effect = EffectBuilder::new()
+                .add_effect(BaseEffect {
+                    kind: BaseEffectType::Strong { magnitude },
+                    scheduling: Replay {
+                        play_for: duration,
+                        ..Default::default()
+                    },
+                    envelope: Default::default(),
+                })
+                .repeat(Repeat::For(duration))
+                .gamepads(&[request.gamepad])
+                .finish(&mut self.gilrs);
+
+            match effect.and_then(|effect| effect.play().map(|()| effect)) {
+                Ok(effect) => {
+                    self.rumble_effects.insert(request.gamepad, effect);
+                }
+                Err(err) => trace_error!("failed to play rumble effect: {err}"),
+            }
+        }
+    }
+
+    fn handle_gilrs_events(&mut self, event_loop: &ActiveEventLoop) -> bool {
+        trace_span!("gamepad_events");
+
+        let flow = Cell::new(GameFlow::Continue);
+
+        while flow.get() == GameFlow::Continue
+            && let Some(event) = self.gilrs.next_event()
+        {
             match event.event {
                 gilrs::EventType::AxisChanged(axis, value, _) => {
                     let (positive_code, negative_code) = match axis {
@@ -318,25 +921,49 @@ impl InitRunner {
                             (ButtonCode::RightStickUp, ButtonCode::RightStickDown)
                         }
                         gilrs::Axis::Unknown => (ButtonCode::Unknown, ButtonCode::Unknown),
-                        gilrs::Axis::DPadX => continue,
-                        gilrs::Axis::DPadY => continue,
+                        gilrs::Axis::DPadX => (ButtonCode::DPadRight, ButtonCode::DPadLeft),
+                        gilrs::Axis::DPadY => (ButtonCode::DPadUp, ButtonCode::DPadDown),
                         gilrs::Axis::LeftZ => continue,
                         gilrs::Axis::RightZ => continue,
                     };
 
-                    self.game.event(
+                    if let gilrs::Axis::DPadX | gilrs::Axis::DPadY = axis {
+                        let changes = dpad_axis_changes(
+                            &mut self.dpad_held,
+                            event.id,
+                            (positive_code, negative_code),
+                            value,
+                        );
+
+                        for (code, is_held) in changes {
+                            self.game.borrow_mut().event(
+                                &GameEvent::Button {
+                                    gamepad: event.id,
+                                    code,
+                                    value: if is_held { 1.0 } else { 0.0 },
+                                },
+                                self.context(Some(&flow)),
+                            );
+                        }
+
+                        continue;
+                    }
+
+                    self.game.borrow_mut().event(
                         &GameEvent::Button {
+                            gamepad: event.id,
                             code: positive_code,
                             value: value.max(0.0),
                         },
-                        ctx,
+                        self.context(Some(&flow)),
                     );
-                    self.game.event(
+                    self.game.borrow_mut().event(
                         &GameEvent::Button {
+                            gamepad: event.id,
                             code: negative_code,
                             value: (-value).max(0.0),
                         },
-                        ctx,
+                        self.context(Some(&flow)),
                     );
                 }
                 gilrs::EventType::ButtonChanged(button, value, _) => {
@@ -363,23 +990,223 @@ impl InitRunner {
                         gilrs::Button::Unknown => ButtonCode::Unknown,
                     };
 
-                    self.game.event(&GameEvent::Button { code, value }, ctx);
+                    if let ButtonCode::DPadUp
+                    | ButtonCode::DPadDown
+                    | ButtonCode::DPadLeft
+                    | ButtonCode::DPadRight = code
+                    {
+                        self.dpad_held.insert((event.id, code), value >= 0.5);
+                    }
+
+                    self.game.borrow_mut().event(
+                        &GameEvent::Button {
+                            gamepad: event.id,
+                            code,
+                            value,
+                        },
+                        self.context(Some(&flow)),
+                    );
+                }
+                gilrs::EventType::Connected => {
+                    self.game.borrow_mut().event(
+                        &GameEvent::GamepadConnected(event.id),
+                        self.context(Some(&flow)),
+                    );
+                }
+                gilrs::EventType::Disconnected => {
+                    self.dpad_held
+                        .retain(|(gamepad, _), _| *gamepad != event.id);
+
+                    self.game.borrow_mut().event(
+                        &GameEvent::GamepadDisconnected(event.id),
+                        self.context(Some(&flow)),
+                    );
                 }
                 _ => {}
             }
         }
 
-        if should_exit.load(std::sync::atomic::Ordering::Relaxed) {
-            self.game.end(GameContext {
-                window: &self.window,
-                device: &self.device,
-                queue: &self.queue,
-                surface_format: self.surface_config.format,
-                should_exit: None,
-            });
+        self.apply_flow(flow.get(), event_loop)
+    }
+
+    fn handle_device_lost(&mut self, event_loop: &ActiveEventLoop) -> bool {
+        let flow = Cell::new(GameFlow::Continue);
+
+        while flow.get() == GameFlow::Continue
+            && let Ok((reason, message)) = self.device_lost.try_recv()
+        {
+            trace_error!("device lost ({reason:?}): {message}");
+
+            self.game.borrow_mut().event(
+                &GameEvent::DeviceLost { reason, message },
+                self.context(Some(&flow)),
+            );
+        }
+
+        self.apply_flow(flow.get(), event_loop)
+    }
 
+    fn apply_flow(&mut self, flow: GameFlow, event_loop: &ActiveEventLoop) -> bool {
+        if flow == GameFlow::Continue {
+            return false;
+        }
+
+        if flow == GameFlow::Exit {
+            self.end();
             event_loop.exit();
+            return true;
+        }
+
+        self.game.borrow_mut().end(self.context(None));
+
+        match Game::new(self.context(None)) {
+            Ok(game) => self.game = RefCell::new(game),
+            Err(err) => {
+                // The old game has already ended, so there is nothing left to run.
+                self.has_ended = true;
+                self.fatal_error = Some(RunError::Game(err));
+                event_loop.exit();
+                return true;
+            }
+        }
+
+        self.fixed_update_lag = Duration::ZERO;
+
+        false
+    }
+
+    /// Ends the game for good. Runs `Game::end` only the first time, since winit can keep
+    /// delivering events and calls `exiting` after an exit was requested.
+    fn end(&mut self) {
+        if self.has_ended {
             return;
         }
+
+        self.has_ended = true;
+
+        self.game.borrow_mut().end(self.context(None));
+    }
+}
+
+/// Writes a new window size into the surface config. Returns `false` without touching it for
+/// the 0x0 size of a minimized window, which some backends fail to configure a surface with.
+/// The size to resize the surface to after `event`. Only `Resized` carries one, since the
+/// window hasn't taken its new size yet during `ScaleFactorChanged`. Winit follows a scale
+/// change with `Resized` whenever the size changes with it.
+fn surface_resize(event: &WindowEvent) -> Option<PhysicalSize<u32>> {
+    match event {
+        WindowEvent::Resized(new_size) => Some(*new_size),
+        _ => None,
+    }
+}
+
+fn resize_surface_config(config: &mut SurfaceConfiguration, new_size: PhysicalSize<u32>) -> bool {
+    if new_size.width == 0 || new_size.height == 0 {
+        return false;
+    }
+
+    config.width = new_size.width;
+    config.height = new_size.height;
+
+    true
+}
+
+/// The DPad buttons an axis-style DPad value presses or releases. Buttons already in that
+/// state are skipped, so controllers that report the DPad both ways send each change once.
+fn dpad_axis_changes(
+    dpad_held: &mut HashMap<(GamepadId, ButtonCode), bool>,
+    gamepad: GamepadId,
+    (positive_code, negative_code): (ButtonCode, ButtonCode),
+    value: f32,
+) -> Vec<(ButtonCode, bool)> {
+    [
+        (positive_code, value >= 0.5),
+        (negative_code, value <= -0.5),
+    ]
+    .into_iter()
+    .filter(|&(code, is_held)| {
+        dpad_held.insert((gamepad, code), is_held).unwrap_or(false) != is_held
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::{CompositeAlphaMode, TextureUsages};
+    use winit::dpi::PhysicalPosition;
+
+    use super::*;
+    use crate::input::test_events::gamepad;
+
+    const DPAD_X: (ButtonCode, ButtonCode) = (ButtonCode::DPadRight, ButtonCode::DPadLeft);
+
+    #[test]
+    fn minimizing_keeps_the_surface_size() {
+        let mut config = SurfaceConfiguration {
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            format: TextureFormat::Bgra8UnormSrgb,
+            width: 1280,
+            height: 720,
+            present_mode: PresentMode::Fifo,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: CompositeAlphaMode::Auto,
+            view_formats: Vec::new(),
+        };
+
+        assert!(!resize_surface_config(&mut config, PhysicalSize::new(0, 0)));
+        assert!(!resize_surface_config(
+            &mut config,
+            PhysicalSize::new(1280, 0)
+        ));
+        assert_eq!((config.width, config.height), (1280, 720));
+
+        assert!(resize_surface_config(
+            &mut config,
+            PhysicalSize::new(800, 600)
+        ));
+        assert_eq!((config.width, config.height), (800, 600));
+    }
+
+    #[test]
+    fn dpad_axis_presses_and_releases_buttons() {
+        let mut dpad_held = HashMap::new();
+        let gamepad = gamepad(0);
+
+        assert_eq!(
+            dpad_axis_changes(&mut dpad_held, gamepad, DPAD_X, 1.0),
+            [(ButtonCode::DPadRight, true)]
+        );
+        assert_eq!(
+            dpad_axis_changes(&mut dpad_held, gamepad, DPAD_X, -1.0),
+            [(ButtonCode::DPadRight, false), (ButtonCode::DPadLeft, true)]
+        );
+        assert_eq!(
+            dpad_axis_changes(&mut dpad_held, gamepad, DPAD_X, 0.2),
+            [(ButtonCode::DPadLeft, false)]
+        );
+    }
+
+    #[test]
+    fn dpad_axis_skips_buttons_already_held() {
+        let mut dpad_held = HashMap::new();
+        let gamepad = gamepad(0);
+
+        // What the button-style path records for the same press.
+        dpad_held.insert((gamepad, ButtonCode::DPadRight), true);
+
+        assert_eq!(dpad_axis_changes(&mut dpad_held, gamepad, DPAD_X, 1.0), []);
+    }
+
+    #[test]
+    fn only_resized_events_resize_the_surface() {
+        assert_eq!(
+            surface_resize(&WindowEvent::Resized(PhysicalSize::new(800, 600))),
+            Some(PhysicalSize::new(800, 600))
+        );
+        assert_eq!(surface_resize(&WindowEvent::Focused(true)), None);
+        assert_eq!(
+            surface_resize(&WindowEvent::Moved(PhysicalPosition::new(10, 20))),
+            None
+        );
     }
 }
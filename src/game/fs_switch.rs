@@ -16,7 +16,7 @@ impl FsSwitch {
     }
 
     pub fn event(&mut self, event: &GameEvent, ctx: GameContext) {
-        let GameEvent::Key { code, is_held } = event else {
+        let GameEvent::Key { code, is_held, .. } = event else {
             return;
         };
 
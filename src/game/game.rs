@@ -1,80 +1,160 @@
+use std::{error::Error, time::Duration};
+
 use glam::{Vec2, vec2};
-use wgpu::TextureView;
-use winit::window::{Window, WindowAttributes};
+use image::imageops;
+use wgpu::{DeviceDescriptor, PresentMode, RequestAdapterOptions, TextureView};
+use winit::{
+    dpi::LogicalSize,
+    keyboard::KeyCode,
+    window::{Icon, Window, WindowAttributes},
+};
 
 use crate::{
-    game::{FsSwitch, GameContext, GameEvent, Time},
+    asset_path,
+    game::{Assets, FsSwitch, GameClock, GameContext, GameEvent},
     input::{InputBindings, InputHandler},
-    renderer::{Quad, Renderer, Sprite},
+    renderer::{AtlasId, Camera, DebugOverlay, Quad, Renderer, RendererConfig, Sprite},
+    trace_warn,
 };
 
 #[derive(Debug)]
 pub struct Game {
-    time: Time,
+    clock: GameClock,
     fs_switch: FsSwitch,
     renderer: Renderer,
+    debug_overlay: DebugOverlay,
+    #[cfg_attr(
+        not(feature = "hot-reload"),
+        expect(dead_code, reason = "only used to reload atlases")
+    )]
+    assets: Assets,
+    gray_atlas: AtlasId,
     input: InputHandler,
-    t: f32,
+    bindings: InputBindings,
     pos: Vec2,
 }
 
 impl Game {
+    pub const FIXED_TIMESTEP: Option<Duration> = None;
+    pub const MAX_FIXED_UPDATES_PER_FRAME: u32 = 5;
+    /// Caps the frame rate by sleeping between frames. `None` and `Some(0)` leave it uncapped.
+    pub const TARGET_FPS: Option<u32> = None;
+    pub const PRESENT_MODE: PresentMode = PresentMode::Fifo;
+
     pub fn window_attributes() -> WindowAttributes {
         Window::default_attributes()
             .with_title("Drill Game")
+            .with_window_icon(Self::window_icon())
+            .with_inner_size(LogicalSize::new(1280, 720))
+            .with_min_inner_size(LogicalSize::new(320, 180))
             .with_maximized(true)
     }
 
-    pub fn new(ctx: GameContext) -> Self {
-        Self {
-            time: Time::new(),
+    pub fn window_icon() -> Option<Icon> {
+        let atlas = match image::open(asset_path!("sprite_atlas.png")) {
+            Ok(atlas) => atlas.to_rgba8(),
+            Err(err) => {
+                trace_warn!("failed to open window icon: {err}");
+                return None;
+            }
+        };
+
+        let icon =
+            imageops::crop_imm(&atlas, 0, 0, atlas.width() / 20, atlas.height() / 20).to_image();
+
+        Icon::from_rgba(icon.to_vec(), icon.width(), icon.height())
+            .inspect_err(|err| trace_warn!("failed to create window icon: {err}"))
+            .ok()
+    }
+
+    pub fn adapter_options() -> RequestAdapterOptions<'static, 'static> {
+        RequestAdapterOptions::default()
+    }
+
+    pub fn device_descriptor() -> DeviceDescriptor<'static> {
+        DeviceDescriptor::default()
+    }
+
+    /// Errors end the run with `RunError::Game`.
+    pub fn new(ctx: GameContext) -> Result<Self, Box<dyn Error>> {
+        let mut renderer = Renderer::new(&RendererConfig::default(), ctx.into())?;
+
+        let gray_atlas = image::open(asset_path!("sprite_atlas.png"))?
+            .grayscale()
+            .to_rgba8();
+
+        let gray_atlas = renderer.load_atlas(&gray_atlas, ctx.into());
+
+        let mut debug_overlay = DebugOverlay::new(renderer.load_debug_font(ctx.into()));
+        debug_overlay.is_enabled = false;
+
+        let mut assets = Assets::new();
+        assets.watch_atlas(asset_path!("sprite_atlas.png"), AtlasId::default());
+
+        Ok(Self {
+            clock: GameClock::new(),
             fs_switch: FsSwitch::new(),
-            renderer: Renderer::new(ctx.into()),
+            renderer,
+            debug_overlay,
+            assets,
+            gray_atlas,
             input: InputHandler::new(&InputBindings::default()),
-            t: 0.0,
+            bindings: InputBindings::default(),
             pos: Vec2::ZERO,
-        }
+        })
     }
 
-    pub fn update(&mut self, _: GameContext) {
-        let dt = self.time.tick();
-        self.t += dt;
+    pub fn update(&mut self, ctx: GameContext) {
+        #[cfg(feature = "hot-reload")]
+        self.assets.reload_changed(&mut self.renderer, ctx.into());
+
+        self.clock.tick(ctx.delta);
+        self.debug_overlay.record(self.clock.real_delta());
+        let dt = self.clock.scaled_delta().as_secs_f32();
+
+        if let Some(source) = self.input.capture_next_input() {
+            self.bindings.jump.bind(source);
+            self.input.set_bindings(&self.bindings);
+        }
 
-        let input = self.input.next_state();
+        let input = self.input.next_state(ctx.delta);
 
-        self.pos += vec2(input.x.value(), input.y.value()) * 10.0 * dt;
+        self.pos += input.movement.value() * 10.0 * dt;
     }
 
+    pub fn fixed_update(&mut self, _: GameContext) {}
+
     pub fn render(&mut self, output: &TextureView, ctx: GameContext) {
         self.renderer.render_frame(
             |r| {
                 r.render_layer(
                     |r| {
-                        r.render_quad(Quad {
-                            center: self.pos,
-                            layer: 0.0,
-                            sprite: Sprite {
-                                center: Vec2::splat(1.0 / 40.0),
-                                extents: Vec2::splat(1.0 / 40.0),
-                            },
-                        })
+                        r.render_quad(Quad::at(self.pos).sprite(Sprite {
+                            center: Vec2::splat(1.0 / 40.0),
+                            extents: Vec2::splat(1.0 / 40.0),
+                        }))
                     },
-                    vec2(3.0, self.t.sin()),
+                    Camera::new(vec2(3.0, self.clock.scaled_elapsed().as_secs_f32().sin())),
                 );
 
                 r.render_layer(
                     |r| {
-                        r.render_quad(Quad {
-                            center: Vec2::ZERO,
-                            layer: 0.0,
-                            sprite: Sprite {
-                                center: Vec2::splat(3.0 / 40.0),
-                                extents: Vec2::splat(1.0 / 40.0),
-                            },
-                        })
+                        let sprite = Sprite {
+                            center: Vec2::splat(3.0 / 40.0),
+                            extents: Vec2::splat(1.0 / 40.0),
+                        };
+
+                        r.render_quad(Quad::at(Vec2::ZERO).sprite(sprite));
+                        r.render_quad(
+                            Quad::at(vec2(2.0, 0.0))
+                                .sprite(sprite)
+                                .atlas(self.gray_atlas),
+                        );
                     },
-                    vec2(0.0, 0.0),
+                    Camera::default(),
                 );
+
+                r.render_debug_overlay(&self.debug_overlay);
             },
             output,
             ctx.into(),
@@ -87,9 +167,30 @@ impl Game {
 
         match event {
             GameEvent::CloseRequested => ctx.exit(),
+            GameEvent::Focused(false) => self.input.release_all(),
+            // Binds jump to whatever is pressed next.
+            GameEvent::Key {
+                code: KeyCode::F1,
+                is_held: true,
+                is_repeat: false,
+            } => self.input.start_capture(),
+            GameEvent::Key {
+                code: KeyCode::F3,
+                is_held: true,
+                is_repeat: false,
+            } => self.debug_overlay.is_enabled = !self.debug_overlay.is_enabled,
+            GameEvent::Key {
+                code: KeyCode::KeyP,
+                is_held: true,
+                is_repeat: false,
+            } => self.clock.paused = !self.clock.paused,
             _ => {}
         }
     }
 
+    pub fn suspend(&mut self, _: GameContext) {}
+
+    pub fn resume(&mut self, _: GameContext) {}
+
     pub fn end(&mut self, _: GameContext) {}
 }
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+/// Gameplay time that can be paused and slowed down or sped up, alongside the real time UI
+/// animations keep running on.
+#[derive(Debug, Clone)]
+pub struct GameClock {
+    pub time_scale: f32,
+    pub paused: bool,
+    real_delta: Duration,
+    scaled_delta: Duration,
+    scaled_elapsed: Duration,
+}
+
+impl GameClock {
+    /// The highest `time_scale` the clock honors, so huge or infinite scales can't overflow.
+    pub const MAX_TIME_SCALE: f32 = 1000.0;
+
+    pub fn new() -> Self {
+        Self {
+            time_scale: 1.0,
+            paused: false,
+            real_delta: Duration::ZERO,
+            scaled_delta: Duration::ZERO,
+            scaled_elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Advances the clock by a frame's real delta, such as `GameContext::delta`. Negative and NaN
+    /// time scales count as 0.
+    pub fn tick(&mut self, real_delta: Duration) {
+        self.real_delta = real_delta;
+        self.scaled_delta = if self.paused || self.time_scale.is_nan() {
+            Duration::ZERO
+        } else {
+            real_delta.mul_f32(self.time_scale.clamp(0.0, Self::MAX_TIME_SCALE))
+        };
+        self.scaled_elapsed += self.scaled_delta;
+    }
+
+    pub fn real_delta(&self) -> Duration {
+        self.real_delta
+    }
+
+    pub fn scaled_delta(&self) -> Duration {
+        self.scaled_delta
+    }
+
+    /// Gameplay time since the clock was created, not counting pauses.
+    pub fn scaled_elapsed(&self) -> Duration {
+        self.scaled_elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A quarter second, which f32 scaling keeps exact.
+    const FRAME: Duration = Duration::from_millis(250);
+
+    #[test]
+    fn pausing_stops_gameplay_time_only() {
+        let mut clock = GameClock::new();
+        clock.tick(FRAME);
+
+        clock.paused = true;
+        clock.tick(FRAME);
+        clock.tick(FRAME * 2);
+
+        assert_eq!(clock.real_delta(), FRAME * 2);
+        assert_eq!(clock.scaled_delta(), Duration::ZERO);
+        assert_eq!(clock.scaled_elapsed(), FRAME);
+    }
+
+    #[test]
+    fn time_scale_scales_gameplay_time() {
+        let mut clock = GameClock::new();
+        clock.time_scale = 0.5;
+        clock.tick(FRAME);
+        clock.time_scale = 2.0;
+        clock.tick(FRAME);
+
+        assert_eq!(clock.real_delta(), FRAME);
+        assert_eq!(clock.scaled_delta(), FRAME * 2);
+        assert_eq!(clock.scaled_elapsed(), FRAME / 2 + FRAME * 2);
+    }
+
+    #[test]
+    fn out_of_range_time_scales_are_clamped() {
+        let mut clock = GameClock::new();
+
+        clock.time_scale = f32::INFINITY;
+        clock.tick(FRAME);
+        assert_eq!(
+            clock.scaled_delta(),
+            FRAME.mul_f32(GameClock::MAX_TIME_SCALE)
+        );
+
+        clock.time_scale = f32::NAN;
+        clock.tick(FRAME);
+        assert_eq!(clock.scaled_delta(), Duration::ZERO);
+
+        clock.time_scale = -1.0;
+        clock.tick(FRAME);
+        assert_eq!(clock.scaled_delta(), Duration::ZERO);
+    }
+}
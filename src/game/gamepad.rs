@@ -0,0 +1,64 @@
+use gilrs::{Gamepad, GamepadId};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GamepadInfo {
+    pub id: GamepadId,
+    pub name: String,
+    pub kind: GamepadKind,
+}
+
+/// The controller family, for showing matching button prompts. Detected from the USB vendor
+/// when the platform reports it and from the name otherwise, so it is a best guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadKind {
+    Xbox,
+    PlayStation,
+    Other,
+}
+
+const MICROSOFT_VENDOR_ID: u16 = 0x045e;
+const SONY_VENDOR_ID: u16 = 0x054c;
+
+impl GamepadInfo {
+    pub(in crate::game) fn new(id: GamepadId, gamepad: Gamepad) -> Self {
+        Self {
+            id,
+            name: gamepad.name().to_string(),
+            kind: GamepadKind::detect(gamepad.vendor_id(), gamepad.name()),
+        }
+    }
+}
+
+impl GamepadKind {
+    fn detect(vendor_id: Option<u16>, name: &str) -> Self {
+        match vendor_id {
+            Some(MICROSOFT_VENDOR_ID) => return Self::Xbox,
+            Some(SONY_VENDOR_ID) => return Self::PlayStation,
+            _ => {}
+        }
+
+        let name = name.to_lowercase();
+
+        if ["xbox", "xinput", "microsoft"]
+            .iter()
+            .any(|word| name.contains(word))
+        {
+            Self::Xbox
+        } else if [
+            "playstation",
+            "dualshock",
+            "dualsense",
+            "ps3",
+            "ps4",
+            "ps5",
+            "sony",
+        ]
+        .iter()
+        .any(|word| name.contains(word))
+        {
+            Self::PlayStation
+        } else {
+            Self::Other
+        }
+    }
+}
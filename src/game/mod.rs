@@ -1,8 +1,18 @@
+mod assets;
 mod context;
 mod fs_switch;
+#[expect(clippy::module_inception)]
 mod game;
-mod time;
+mod game_clock;
+mod gamepad;
+mod rng;
+mod screen_fade;
+pub use assets::*;
 pub use context::*;
 pub use fs_switch::*;
 pub use game::*;
-pub use time::*;
+pub use game_clock::*;
+pub use gamepad::*;
+pub use rng::*;
+#[expect(unused_imports, reason = "the demo has no transitions to fade")]
+pub use screen_fade::*;
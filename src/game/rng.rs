@@ -0,0 +1,80 @@
+use std::ops::Range;
+
+use glam::{Vec2, vec2};
+
+/// A small xorshift generator. The same seed always gives the same sequence, which keeps
+/// gameplay randomness reproducible for replays.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Self { state: seed.max(1) }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+
+        self.state
+    }
+
+    /// A number in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    pub fn range(&mut self, range: Range<f32>) -> f32 {
+        range.start + (range.end - range.start) * self.next_f32()
+    }
+
+    #[cfg_attr(
+        not(test),
+        expect(dead_code, reason = "nothing in the demo is scattered randomly yet")
+    )]
+    pub fn in_unit_circle(&mut self) -> Vec2 {
+        loop {
+            let point = vec2(self.range(-1.0..1.0), self.range(-1.0..1.0));
+
+            if point.length_squared() < 1.0 {
+                return point;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_the_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+
+        assert_ne!(Rng::new(42).next_u32(), Rng::new(43).next_u32());
+    }
+
+    #[test]
+    fn zero_seed_still_generates() {
+        assert_ne!(Rng::new(0).next_u32(), 0);
+    }
+
+    #[test]
+    fn outputs_stay_in_range() {
+        let mut rng = Rng::new(7);
+
+        for _ in 0..1000 {
+            assert!((0.0..1.0).contains(&rng.next_f32()));
+            assert!((-3.0..5.0).contains(&rng.range(-3.0..5.0)));
+            assert!(rng.in_unit_circle().length_squared() < 1.0);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use glam::Vec4;
+
+use crate::renderer::Renderer;
+
+#[derive(Debug, Clone)]
+pub struct ScreenFade {
+    from: Vec4,
+    to: Vec4,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+#[expect(dead_code, reason = "the demo has no transitions to fade")]
+impl ScreenFade {
+    pub fn new() -> Self {
+        Self {
+            from: Vec4::ZERO,
+            to: Vec4::ZERO,
+            duration: Duration::ZERO,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Fades from the current color to `to` over `duration`.
+    ///
+    /// `ScreenFade::fade_to(Vec4::W, ..)` fades to black and `fade_to(Vec4::ZERO, ..)` fades
+    /// back in.
+    pub fn fade_to(&mut self, to: Vec4, duration: Duration) {
+        self.from = self.color();
+        self.to = to;
+        self.duration = duration;
+        self.elapsed = Duration::ZERO;
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    pub fn color(&self) -> Vec4 {
+        if self.is_done() {
+            return self.to;
+        }
+
+        let t = self.elapsed.as_secs_f32() / self.duration.as_secs_f32();
+
+        self.from.lerp(self.to, t)
+    }
+
+    pub fn apply(&self, renderer: &mut Renderer) {
+        renderer.set_fade(self.color());
+    }
+}
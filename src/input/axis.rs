@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::{
     game::GameEvent,
     input::{
@@ -15,6 +17,18 @@ pub struct Axis<T> {
 pub struct AxisBindings {
     pub positive: ButtonBindings,
     pub negative: ButtonBindings,
+    /// Eases digital value axes toward their target instead of jumping. Ignored by button axes.
+    pub smoothing: Option<AxisSmoothing>,
+}
+
+/// How fast a smoothed value follows its keys, in units per second.
+///
+/// Gamepad buttons and sticks are analog already, so while one of them is held the value
+/// follows it directly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisSmoothing {
+    pub attack: f32,
+    pub decay: f32,
 }
 
 #[derive(Debug, Default)]
@@ -42,10 +56,15 @@ impl AxisHandler<ButtonHandler> {
         self.negative.event(event, stick_handler);
     }
 
-    pub fn next_state(&mut self) -> Axis<Button> {
+    pub fn release_all(&mut self) {
+        self.positive.release_all();
+        self.negative.release_all();
+    }
+
+    pub fn next_state(&mut self, delta: Duration) -> Axis<Button> {
         Axis {
-            positive: self.positive.next_state(),
-            negative: self.negative.next_state(),
+            positive: self.positive.next_state(delta),
+            negative: self.negative.next_state(delta),
         }
     }
 }
@@ -53,8 +72,8 @@ impl AxisHandler<ButtonHandler> {
 impl AxisHandler<ValueHandler> {
     pub fn new(bindings: &AxisBindings) -> Self {
         Self {
-            positive: ValueHandler::new(&bindings.positive),
-            negative: ValueHandler::new(&bindings.negative),
+            positive: ValueHandler::new(&bindings.positive).with_smoothing(bindings.smoothing),
+            negative: ValueHandler::new(&bindings.negative).with_smoothing(bindings.smoothing),
         }
     }
 
@@ -63,10 +82,15 @@ impl AxisHandler<ValueHandler> {
         self.negative.event(event);
     }
 
-    pub fn next_state(&mut self) -> Axis<Value> {
+    pub fn release_all(&mut self) {
+        self.positive.release_all();
+        self.negative.release_all();
+    }
+
+    pub fn next_state(&mut self, delta: Duration) -> Axis<Value> {
         Axis {
-            positive: self.positive.next_state(),
-            negative: self.negative.next_state(),
+            positive: self.positive.next_state(delta),
+            negative: self.negative.next_state(delta),
         }
     }
 }
@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use glam::{Vec2, vec2};
+
+use crate::{
+    game::GameEvent,
+    input::{Axis, AxisBindings, AxisHandler, Value, ValueHandler},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Axis2<T> {
+    pub x: Axis<T>,
+    pub y: Axis<T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Axis2Bindings {
+    pub x: AxisBindings,
+    pub y: AxisBindings,
+    pub deadzone: f32,
+}
+
+#[derive(Debug, Default)]
+pub(in crate::input) struct Axis2Handler<T> {
+    x: AxisHandler<T>,
+    y: AxisHandler<T>,
+    deadzone: f32,
+}
+
+impl Axis2<Value> {
+    pub fn value(&self) -> Vec2 {
+        vec2(self.x.value(), self.y.value())
+    }
+}
+
+impl Axis2Handler<ValueHandler> {
+    pub fn new(bindings: &Axis2Bindings) -> Self {
+        Self {
+            x: AxisHandler::<ValueHandler>::new(&bindings.x),
+            y: AxisHandler::<ValueHandler>::new(&bindings.y),
+            deadzone: bindings.deadzone,
+        }
+    }
+
+    pub fn event(&mut self, event: &GameEvent) {
+        self.x.event(event);
+        self.y.event(event);
+    }
+
+    pub fn release_all(&mut self) {
+        self.x.release_all();
+        self.y.release_all();
+    }
+
+    pub fn next_state(&mut self, delta: Duration) -> Axis2<Value> {
+        let state = Axis2 {
+            x: self.x.next_state(delta),
+            y: self.y.next_state(delta),
+        };
+
+        let length = state.value().length();
+
+        let scale = if length < self.deadzone {
+            0.0
+        } else if length > 1.0 {
+            1.0 / length
+        } else {
+            return state;
+        };
+
+        let scale_axis = |axis: Axis<Value>| Axis {
+            positive: Value(axis.positive.0 * scale),
+            negative: Value(axis.negative.0 * scale),
+        };
+
+        Axis2 {
+            x: scale_axis(state.x),
+            y: scale_axis(state.y),
+        }
+    }
+}
@@ -1,10 +1,13 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
-use winit::keyboard::KeyCode;
+use winit::{event::MouseButton, keyboard::KeyCode};
 
 use crate::{
     game::{ButtonCode, GameEvent},
-    input::stick_handler::StickHandler,
+    input::{InputSource, ResponseCurve, stick_handler::StickHandler},
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
@@ -12,21 +15,93 @@ pub struct Button {
     pub is_held: bool,
     pub is_pressed: bool,
     pub is_released: bool,
+    pub held_for: Duration,
+    pub double_tapped: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ButtonBindings {
     pub keys: HashSet<KeyCode>,
     pub buttons: HashSet<ButtonCode>,
+    pub mouse_buttons: HashSet<MouseButton>,
+    pub stick_min_dot: f32,
+    pub stick_activation: f32,
+    pub double_tap_window: Duration,
+    pub response_curve: ResponseCurve,
 }
 
 #[derive(Debug, Default)]
 pub(in crate::input) struct ButtonHandler {
-    key_indices: HashMap<KeyCode, u8>,
-    button_indices: HashMap<ButtonCode, u8>,
-    held_bindings: u32,
+    key_indices: HashMap<KeyCode, usize>,
+    button_indices: HashMap<ButtonCode, usize>,
+    mouse_button_indices: HashMap<MouseButton, usize>,
+    stick_min_dot: f32,
+    stick_activation: f32,
+    double_tap_window: Duration,
+    held_bindings: Vec<bool>,
     is_pressed: bool,
     was_held: bool,
+    /// The sum of the deltas passed to `next_state`, which events are timed by.
+    time: Duration,
+    held_since: Option<Duration>,
+    last_tap: Option<Duration>,
+    double_tapped: bool,
+}
+
+impl Button {
+    /// Clears this frame's edges so later reads of the same `Input` don't react to them again.
+    ///
+    /// Let menus read input first and consume what they handle, then pass the same input on
+    /// to gameplay:
+    ///
+    /// ```ignore
+    /// let mut input = input_handler.next_state(ctx.delta);
+    ///
+    /// if menu.is_open() && input.menu_accept.is_pressed {
+    ///     menu.accept();
+    ///     input.jump.consume();
+    /// }
+    ///
+    /// player.update(&input);
+    /// ```
+    #[expect(dead_code, reason = "the demo has no menus that consume presses")]
+    pub fn consume(&mut self) {
+        self.is_pressed = false;
+        self.is_released = false;
+        self.double_tapped = false;
+    }
+}
+
+impl ButtonBindings {
+    /// Replaces every key, button and mouse button with `source`, keeping the other settings.
+    ///
+    /// Assigning the sets directly, like `bindings.keys = HashSet::from_iter([code])`, also
+    /// replaces the old keys but leaves gamepad and mouse bindings in place.
+    pub fn bind(&mut self, source: InputSource) {
+        self.keys.clear();
+        self.buttons.clear();
+        self.mouse_buttons.clear();
+
+        match source {
+            InputSource::Key(code) => _ = self.keys.insert(code),
+            InputSource::Button(code) => _ = self.buttons.insert(code),
+            InputSource::MouseButton(button) => _ = self.mouse_buttons.insert(button),
+        }
+    }
+}
+
+impl Default for ButtonBindings {
+    fn default() -> Self {
+        Self {
+            keys: HashSet::new(),
+            buttons: HashSet::new(),
+            mouse_buttons: HashSet::new(),
+            stick_min_dot: 0.3827,
+            stick_activation: 0.5,
+            double_tap_window: Duration::from_millis(250),
+            response_curve: ResponseCurve::Linear,
+        }
+    }
 }
 
 impl ButtonHandler {
@@ -36,7 +111,7 @@ impl ButtonHandler {
             .iter()
             .copied()
             .enumerate()
-            .map(|(index, code)| (code, index as u8))
+            .map(|(index, code)| (code, index))
             .collect();
 
         let button_indices = bindings
@@ -44,24 +119,57 @@ impl ButtonHandler {
             .iter()
             .copied()
             .enumerate()
-            .map(|(index, code)| (code, index as u8))
+            .map(|(index, code)| (code, bindings.keys.len() + index))
+            .collect();
+
+        let mouse_button_indices = bindings
+            .mouse_buttons
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(index, button)| (button, bindings.keys.len() + bindings.buttons.len() + index))
             .collect();
 
         Self {
             key_indices,
             button_indices,
-            held_bindings: 0,
+            mouse_button_indices,
+            stick_min_dot: bindings.stick_min_dot,
+            stick_activation: bindings.stick_activation,
+            double_tap_window: bindings.double_tap_window,
+            held_bindings: vec![
+                false;
+                bindings.keys.len()
+                    + bindings.buttons.len()
+                    + bindings.mouse_buttons.len()
+            ],
             is_pressed: false,
             was_held: false,
+            time: Duration::ZERO,
+            held_since: None,
+            last_tap: None,
+            double_tapped: false,
         }
     }
 
     pub fn event(&mut self, event: &GameEvent, stick_handler: &StickHandler) {
+        if let GameEvent::GamepadDisconnected(_) = event {
+            for index in self.button_indices.values() {
+                self.held_bindings[*index] = false;
+            }
+
+            if !self.held_bindings.contains(&true) {
+                self.held_since = None;
+            }
+
+            return;
+        }
+
         let binding_index;
         let binding_is_held;
 
         match event {
-            GameEvent::Key { code, is_held } => {
+            GameEvent::Key { code, is_held, .. } => {
                 if let Some(index) = self.key_indices.get(code) {
                     binding_index = *index;
                     binding_is_held = *is_held;
@@ -70,10 +178,8 @@ impl ButtonHandler {
                 }
             }
 
-            GameEvent::Button { code, value } => {
+            GameEvent::Button { code, value, .. } => {
                 if let Some(index) = self.button_indices.get(code) {
-                    const STICK_DIR_DOT: f32 = 0.3827;
-
                     let is_held = match code {
                         ButtonCode::DPadRight
                         | ButtonCode::DPadLeft
@@ -97,29 +203,37 @@ impl ButtonHandler {
                         | ButtonCode::Unknown => *value >= 0.5,
 
                         ButtonCode::LeftStickRight => {
-                            *value >= 0.5 && stick_handler.left_stick_dir().x >= STICK_DIR_DOT
+                            *value >= self.stick_activation
+                                && stick_handler.left_stick_dir().x >= self.stick_min_dot
                         }
                         ButtonCode::LeftStickLeft => {
-                            *value >= 0.5 && -stick_handler.left_stick_dir().x >= STICK_DIR_DOT
+                            *value >= self.stick_activation
+                                && -stick_handler.left_stick_dir().x >= self.stick_min_dot
                         }
                         ButtonCode::LeftStickUp => {
-                            *value >= 0.5 && stick_handler.left_stick_dir().y >= STICK_DIR_DOT
+                            *value >= self.stick_activation
+                                && stick_handler.left_stick_dir().y >= self.stick_min_dot
                         }
                         ButtonCode::LeftStickDown => {
-                            *value >= 0.5 && -stick_handler.left_stick_dir().y >= STICK_DIR_DOT
+                            *value >= self.stick_activation
+                                && -stick_handler.left_stick_dir().y >= self.stick_min_dot
                         }
 
                         ButtonCode::RightStickRight => {
-                            *value >= 0.5 && stick_handler.right_stick_dir().x >= STICK_DIR_DOT
+                            *value >= self.stick_activation
+                                && stick_handler.right_stick_dir().x >= self.stick_min_dot
                         }
                         ButtonCode::RightStickLeft => {
-                            *value >= 0.5 && -stick_handler.right_stick_dir().x >= STICK_DIR_DOT
+                            *value >= self.stick_activation
+                                && -stick_handler.right_stick_dir().x >= self.stick_min_dot
                         }
                         ButtonCode::RightStickUp => {
-                            *value >= 0.5 && stick_handler.right_stick_dir().y >= STICK_DIR_DOT
+                            *value >= self.stick_activation
+                                && stick_handler.right_stick_dir().y >= self.stick_min_dot
                         }
                         ButtonCode::RightStickDown => {
-                            *value >= 0.5 && -stick_handler.right_stick_dir().y >= STICK_DIR_DOT
+                            *value >= self.stick_activation
+                                && -stick_handler.right_stick_dir().y >= self.stick_min_dot
                         }
                     };
 
@@ -130,30 +244,184 @@ impl ButtonHandler {
                 }
             }
 
+            GameEvent::MouseButton { button, is_held } => {
+                if let Some(index) = self.mouse_button_indices.get(button) {
+                    binding_index = *index;
+                    binding_is_held = *is_held;
+                } else {
+                    return;
+                }
+            }
+
             _ => return,
         };
 
-        let binding_mask = 1 << binding_index;
-        let binding_was_held = self.held_bindings & binding_mask != 0;
-
-        if binding_is_held && !binding_was_held {
+        if binding_is_held && !self.held_bindings[binding_index] {
             self.is_pressed = true;
         }
 
-        self.held_bindings =
-            (self.held_bindings & !binding_mask) | (binding_mask * binding_is_held as u32);
+        self.held_bindings[binding_index] = binding_is_held;
+
+        if !self.held_bindings.contains(&true) {
+            self.held_since = None;
+        } else if self.held_since.is_none() {
+            let now = self.time;
+            self.held_since = Some(now);
+
+            match self.last_tap {
+                Some(last_tap) if now - last_tap <= self.double_tap_window => {
+                    self.double_tapped = true;
+                    self.last_tap = None;
+                }
+                _ => self.last_tap = Some(now),
+            }
+        }
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.held_bindings.contains(&true)
     }
 
-    pub fn next_state(&mut self) -> Button {
+    pub fn release_all(&mut self) {
+        self.held_bindings.fill(false);
+        self.held_since = None;
+    }
+
+    /// `delta` is the time since the previous call. Events received before the next call
+    /// count as happening at the end of it.
+    pub fn next_state(&mut self, delta: Duration) -> Button {
+        let is_held = self.held_bindings.contains(&true);
+
         let state = Button {
-            is_held: self.held_bindings != 0,
+            is_held,
             is_pressed: self.is_pressed,
-            is_released: self.was_held && self.held_bindings == 0,
+            is_released: self.was_held && !is_held,
+            held_for: self
+                .held_since
+                .map(|held_since| self.time - held_since)
+                .unwrap_or_default(),
+            double_tapped: self.double_tapped,
         };
 
         self.was_held = state.is_held;
         self.is_pressed = false;
+        self.double_tapped = false;
+        self.time += delta;
 
         state
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::*;
+    use crate::input::test_events::{button, key};
+
+    const WINDOW: Duration = Duration::from_millis(250);
+
+    /// Whether a full push at `degrees` above the x axis holds a `LeftStickUp` binding.
+    fn stick_up_is_held(degrees: f32) -> bool {
+        let bindings = ButtonBindings {
+            buttons: HashSet::from_iter([ButtonCode::LeftStickUp]),
+            stick_activation: 0.2,
+            ..Default::default()
+        };
+
+        let mut stick_handler = StickHandler::new();
+        let mut handler = ButtonHandler::new(&bindings);
+
+        let dir = Vec2::from_angle(degrees.to_radians());
+        for event in [
+            button(ButtonCode::LeftStickRight, dir.x),
+            button(ButtonCode::LeftStickUp, dir.y),
+        ] {
+            stick_handler.event(&event);
+            handler.event(&event, &stick_handler);
+        }
+
+        handler.next_state(Duration::ZERO).is_held
+    }
+
+    #[test]
+    fn stick_min_dot_splits_at_22_5_degrees() {
+        assert_eq!(ButtonBindings::default().stick_min_dot, 0.3827);
+
+        assert!(!stick_up_is_held(22.0));
+        assert!(stick_up_is_held(23.0));
+    }
+
+    /// Presses and releases space `gap` after the previous tap, returning whether it counted
+    /// as a double tap.
+    fn tap_after(handler: &mut ButtonHandler, gap: Duration) -> bool {
+        let stick_handler = StickHandler::new();
+
+        handler.next_state(gap);
+        handler.event(&key(KeyCode::Space, true), &stick_handler);
+        handler.event(&key(KeyCode::Space, false), &stick_handler);
+
+        handler.next_state(Duration::ZERO).double_tapped
+    }
+
+    fn space_handler() -> ButtonHandler {
+        ButtonHandler::new(&ButtonBindings {
+            keys: HashSet::from_iter([KeyCode::Space]),
+            double_tap_window: WINDOW,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn double_tap_window_is_inclusive() {
+        let mut handler = space_handler();
+        assert!(!tap_after(&mut handler, Duration::ZERO));
+        assert!(tap_after(&mut handler, WINDOW));
+
+        let mut handler = space_handler();
+        assert!(!tap_after(&mut handler, Duration::ZERO));
+        assert!(!tap_after(&mut handler, WINDOW + Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn triple_tap_double_taps_once() {
+        let mut handler = space_handler();
+        let gap = Duration::from_millis(100);
+
+        assert!(!tap_after(&mut handler, Duration::ZERO));
+        assert!(tap_after(&mut handler, gap));
+        assert!(!tap_after(&mut handler, gap));
+        assert!(tap_after(&mut handler, gap));
+    }
+
+    #[test]
+    fn holding_is_not_a_double_tap() {
+        let mut handler = space_handler();
+        let stick_handler = StickHandler::new();
+
+        handler.event(&key(KeyCode::Space, true), &stick_handler);
+        handler.next_state(Duration::from_millis(100));
+        handler.event(&key(KeyCode::Space, true), &stick_handler);
+
+        let state = handler.next_state(Duration::ZERO);
+        assert!(state.is_held);
+        assert!(!state.double_tapped);
+        assert_eq!(state.held_for, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn bind_replaces_every_source() {
+        let mut bindings = ButtonBindings {
+            keys: HashSet::from_iter([KeyCode::Space, KeyCode::KeyW]),
+            buttons: HashSet::from_iter([ButtonCode::South]),
+            double_tap_window: WINDOW,
+            ..Default::default()
+        };
+
+        bindings.bind(InputSource::Button(ButtonCode::East));
+
+        assert!(bindings.keys.is_empty());
+        assert_eq!(bindings.buttons, HashSet::from_iter([ButtonCode::East]));
+        assert_eq!(bindings.double_tap_window, WINDOW);
+    }
+}
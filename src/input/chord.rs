@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use crate::{
+    game::GameEvent,
+    input::{Button, ButtonBindings, ButtonHandler, stick_handler::StickHandler},
+};
+
+/// A button that is held only while every one of its parts is held, like a special move
+/// bound to two face buttons.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChordBindings {
+    pub parts: Vec<ButtonBindings>,
+}
+
+#[derive(Debug, Default)]
+pub(in crate::input) struct ChordHandler {
+    parts: Vec<ButtonHandler>,
+    is_held: bool,
+    is_pressed: bool,
+    was_held: bool,
+    time: Duration,
+    held_since: Option<Duration>,
+}
+
+impl ChordHandler {
+    pub fn new(bindings: &ChordBindings) -> Self {
+        Self {
+            parts: bindings.parts.iter().map(ButtonHandler::new).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn event(&mut self, event: &GameEvent, stick_handler: &StickHandler) {
+        for part in &mut self.parts {
+            part.event(event, stick_handler);
+        }
+
+        self.update_held();
+    }
+
+    pub fn release_all(&mut self) {
+        for part in &mut self.parts {
+            part.release_all();
+        }
+
+        self.update_held();
+    }
+
+    /// The press happens when the last part is pressed, in whatever order the parts come in.
+    fn update_held(&mut self) {
+        let is_held = !self.parts.is_empty() && self.parts.iter().all(ButtonHandler::is_held);
+
+        if is_held && !self.is_held {
+            self.is_pressed = true;
+            self.held_since = Some(self.time);
+        } else if !is_held {
+            self.held_since = None;
+        }
+
+        self.is_held = is_held;
+    }
+
+    pub fn next_state(&mut self, delta: Duration) -> Button {
+        let state = Button {
+            is_held: self.is_held,
+            is_pressed: self.is_pressed,
+            is_released: self.was_held && !self.is_held,
+            held_for: self
+                .held_since
+                .map(|held_since| self.time - held_since)
+                .unwrap_or_default(),
+            double_tapped: false,
+        };
+
+        self.was_held = state.is_held;
+        self.is_pressed = false;
+        self.time += delta;
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use winit::keyboard::KeyCode;
+
+    use super::*;
+    use crate::input::test_events::key;
+
+    const PARTS: [KeyCode; 3] = [KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD];
+
+    fn chord() -> ChordHandler {
+        ChordHandler::new(&ChordBindings {
+            parts: PARTS
+                .iter()
+                .map(|code| ButtonBindings {
+                    keys: HashSet::from_iter([*code]),
+                    ..Default::default()
+                })
+                .collect(),
+        })
+    }
+
+    /// Sends the events one frame apart, returning the state of every frame.
+    fn frames(handler: &mut ChordHandler, events: &[(KeyCode, bool)]) -> Vec<Button> {
+        let stick_handler = StickHandler::new();
+
+        events
+            .iter()
+            .map(|(code, is_held)| {
+                handler.event(&key(*code, *is_held), &stick_handler);
+                handler.next_state(Duration::from_millis(16))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn press_fires_once_on_the_last_part_in_any_order() {
+        for order in [[0, 1, 2], [2, 0, 1], [1, 2, 0]] {
+            let mut handler = chord();
+            let events = order.map(|index| (PARTS[index], true));
+
+            let states = frames(&mut handler, &events);
+            let presses = states.iter().filter(|state| state.is_pressed).count();
+
+            assert_eq!(presses, 1, "{order:?}");
+            assert!(states[2].is_pressed && states[2].is_held, "{order:?}");
+        }
+    }
+
+    #[test]
+    fn releasing_one_part_releases_the_chord() {
+        let mut handler = chord();
+        let press = PARTS.map(|code| (code, true));
+        frames(&mut handler, &press);
+
+        let states = frames(
+            &mut handler,
+            &[(KeyCode::KeyS, false), (KeyCode::KeyS, true)],
+        );
+
+        assert!(states[0].is_released && !states[0].is_held);
+        assert!(states[1].is_pressed && states[1].is_held);
+    }
+
+    #[test]
+    fn holding_a_subset_never_fires() {
+        let mut handler = chord();
+
+        let states = frames(
+            &mut handler,
+            &[
+                (KeyCode::KeyA, true),
+                (KeyCode::KeyD, true),
+                (KeyCode::KeyA, false),
+                (KeyCode::KeyS, true),
+            ],
+        );
+
+        assert!(states.iter().all(|state| *state == Button::default()));
+    }
+}
@@ -1,21 +1,26 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
+
+use gilrs::GamepadId;
 
 use winit::keyboard::KeyCode;
 
 use crate::{
     game::GameEvent,
     input::{
-        Axis, AxisBindings, AxisHandler, Button, ButtonBindings, ButtonHandler, Value,
-        ValueHandler, stick_handler::StickHandler,
+        Axis, Axis2, Axis2Bindings, Axis2Handler, AxisBindings, AxisHandler, Button,
+        ButtonBindings, ButtonHandler, ChordBindings, ChordHandler, GamepadStick, InputDevice,
+        InputRecording, InputSource, Stick, StickBindings, Value, ValueHandler,
+        stick_handler::StickHandler,
     },
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Input {
-    pub x: Axis<Value>,
-    pub y: Axis<Value>,
+    pub movement: Axis2<Value>,
+    pub aim: Stick,
     pub jump: Button,
     pub drill: Button,
+    pub special: Button,
 
     pub menu_x: Axis<Button>,
     pub menu_y: Axis<Button>,
@@ -25,10 +30,12 @@ pub struct Input {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct InputBindings {
-    pub x: AxisBindings,
-    pub y: AxisBindings,
+    pub movement: Axis2Bindings,
+    pub aim: StickBindings,
     pub jump: ButtonBindings,
     pub drill: ButtonBindings,
+    /// Held while jump and drill are held together by default.
+    pub special: ChordBindings,
 
     pub menu_x: AxisBindings,
     pub menu_y: AxisBindings,
@@ -38,10 +45,11 @@ pub struct InputBindings {
 
 #[derive(Debug, Default)]
 pub struct InputHandler {
-    x: AxisHandler<ValueHandler>,
-    y: AxisHandler<ValueHandler>,
+    movement: Axis2Handler<ValueHandler>,
+    aim: StickBindings,
     jump: ButtonHandler,
     drill: ButtonHandler,
+    special: ChordHandler,
 
     menu_x: AxisHandler<ButtonHandler>,
     menu_y: AxisHandler<ButtonHandler>,
@@ -49,15 +57,22 @@ pub struct InputHandler {
     menu_cancel: ButtonHandler,
 
     stick_handler: StickHandler,
+    is_capturing: bool,
+    captured_input: Option<InputSource>,
+    last_input_device: InputDevice,
+    gamepad: Option<GamepadId>,
+    recording: Option<InputRecording>,
+    replay: Option<(InputRecording, usize)>,
 }
 
 impl InputHandler {
     pub fn new(bindings: &InputBindings) -> Self {
         Self {
-            x: AxisHandler::<ValueHandler>::new(&bindings.x),
-            y: AxisHandler::<ValueHandler>::new(&bindings.y),
+            movement: Axis2Handler::<ValueHandler>::new(&bindings.movement),
+            aim: bindings.aim.clone(),
             jump: ButtonHandler::new(&bindings.jump),
             drill: ButtonHandler::new(&bindings.drill),
+            special: ChordHandler::new(&bindings.special),
 
             menu_x: AxisHandler::<ButtonHandler>::new(&bindings.menu_x),
             menu_y: AxisHandler::<ButtonHandler>::new(&bindings.menu_y),
@@ -65,16 +80,90 @@ impl InputHandler {
             menu_cancel: ButtonHandler::new(&bindings.menu_cancel),
 
             stick_handler: StickHandler::new(),
+            is_capturing: false,
+            captured_input: None,
+            last_input_device: InputDevice::default(),
+            gamepad: None,
+            recording: None,
+            replay: None,
         }
     }
 
+    pub fn set_bindings(&mut self, bindings: &InputBindings) {
+        *self = Self {
+            stick_handler: std::mem::take(&mut self.stick_handler),
+            is_capturing: self.is_capturing,
+            captured_input: self.captured_input,
+            last_input_device: self.last_input_device,
+            gamepad: self.gamepad,
+            recording: self.recording.take(),
+            replay: self.replay.take(),
+            ..Self::new(bindings)
+        };
+    }
+
+    /// Starts waiting for the next pressed key, gamepad button or mouse button.
+    ///
+    /// A "press any key to bind" flow calls this once, then polls
+    /// `capture_next_input` every frame until it returns the source to bind. `Game` rebinds
+    /// jump this way when F1 is pressed:
+    ///
+    /// ```ignore
+    /// if let Some(source) = input_handler.capture_next_input() {
+    ///     // Replaces the old bindings. Insert into `bindings.jump.keys` to add one instead.
+    ///     bindings.jump.bind(source);
+    ///     input_handler.set_bindings(&bindings);
+    /// }
+    /// ```
+    pub fn start_capture(&mut self) {
+        self.is_capturing = true;
+        self.captured_input = None;
+    }
+
+    pub fn capture_next_input(&mut self) -> Option<InputSource> {
+        self.captured_input.take()
+    }
+
+    /// The device of the latest press, for switching button prompts. Stick drift stays below
+    /// the press threshold, so it doesn't count.
+    #[expect(dead_code, reason = "the demo shows no button prompts")]
+    pub fn last_input_device(&self) -> InputDevice {
+        self.last_input_device
+    }
+
+    /// Only takes button and disconnect events from `gamepad`, so unplugging or using another
+    /// player's gamepad doesn't affect this one. With `None`, the default, every gamepad
+    /// drives the same input and any of them disconnecting releases its gamepad buttons.
+    pub fn set_gamepad(&mut self, gamepad: Option<GamepadId>) {
+        self.gamepad = gamepad;
+    }
+
+    pub fn gamepad(&self) -> Option<GamepadId> {
+        self.gamepad
+    }
+
     pub fn event(&mut self, event: &GameEvent) {
+        if let GameEvent::Button { gamepad, .. } | GameEvent::GamepadDisconnected(gamepad) = event
+            && self.gamepad.is_some_and(|routed| routed != *gamepad)
+        {
+            return;
+        }
+
+        if let Some(source) = InputSource::from_press(event) {
+            self.last_input_device = source.device();
+
+            if self.is_capturing {
+                self.is_capturing = false;
+                self.captured_input = Some(source);
+            }
+        }
+
         self.stick_handler.event(event);
 
-        self.x.event(event);
-        self.y.event(event);
+        self.movement.event(event);
         self.jump.event(event, &self.stick_handler);
         self.drill.event(event, &self.stick_handler);
+        self.special.event(event, &self.stick_handler);
 
         self.menu_x.event(event, &self.stick_handler);
         self.menu_y.event(event, &self.stick_handler);
@@ -82,17 +171,84 @@ impl InputHandler {
         self.menu_cancel.event(event, &self.stick_handler);
     }
 
-    pub fn next_state(&mut self) -> Input {
+    pub fn release_all(&mut self) {
+        self.stick_handler.release_all();
+
+        self.movement.release_all();
+        self.jump.release_all();
+        self.drill.release_all();
+        self.special.release_all();
+
+        self.menu_x.release_all();
+        self.menu_y.release_all();
+        self.menu_accept.release_all();
+        self.menu_cancel.release_all();
+    }
+
+    #[expect(dead_code, reason = "the demo keeps the default deadzone")]
+    pub fn set_stick_deadzone(&mut self, deadzone: f32) {
+        self.stick_handler.set_deadzone(deadzone);
+    }
+
+    /// Starts recording every state `next_state` returns, replacing any unfinished recording.
+    #[cfg_attr(not(test), expect(dead_code, reason = "the demo has no replays"))]
+    pub fn start_recording(&mut self) {
+        self.recording = Some(InputRecording::new());
+    }
+
+    #[cfg_attr(not(test), expect(dead_code, reason = "the demo has no replays"))]
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.recording.take()
+    }
+
+    /// Makes `next_state` return the recorded states one frame at a time, ignoring live input
+    /// until the recording runs out.
+    #[cfg_attr(not(test), expect(dead_code, reason = "the demo has no replays"))]
+    pub fn play(&mut self, recording: InputRecording) {
+        self.replay = Some((recording, 0));
+    }
+
+    #[cfg_attr(not(test), expect(dead_code, reason = "the demo has no replays"))]
+    pub fn is_playing(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// `delta` is the time since the previous call, usually `GameContext::delta`, or the
+    /// timestep when called from `Game::fixed_update`. Held durations, double taps and smoothing
+    /// are measured in it rather than wall-clock time, so replays and tests are deterministic.
+    pub fn next_state(&mut self, delta: Duration) -> Input {
+        let live_input = self.next_live_state(delta);
+
+        let input = match &mut self.replay {
+            Some((recording, index)) if *index < recording.len() => {
+                *index += 1;
+                recording.frames[*index - 1]
+            }
+            _ => {
+                self.replay = None;
+                live_input
+            }
+        };
+
+        if let Some(recording) = &mut self.recording {
+            recording.frames.push(input);
+        }
+
+        input
+    }
+
+    fn next_live_state(&mut self, delta: Duration) -> Input {
         Input {
-            x: self.x.next_state(),
-            y: self.y.next_state(),
-            jump: self.jump.next_state(),
-            drill: self.drill.next_state(),
-
-            menu_x: self.menu_x.next_state(),
-            menu_y: self.menu_y.next_state(),
-            menu_accept: self.menu_accept.next_state(),
-            menu_cancel: self.menu_cancel.next_state(),
+            movement: self.movement.next_state(delta),
+            aim: Stick::map(&self.aim, &self.stick_handler),
+            jump: self.jump.next_state(delta),
+            drill: self.drill.next_state(delta),
+            special: self.special.next_state(delta),
+
+            menu_x: self.menu_x.next_state(delta),
+            menu_y: self.menu_y.next_state(delta),
+            menu_accept: self.menu_accept.next_state(delta),
+            menu_cancel: self.menu_cancel.next_state(delta),
         }
     }
 }
@@ -100,63 +256,192 @@ impl InputHandler {
 impl Default for InputBindings {
     fn default() -> Self {
         Self {
-            x: AxisBindings {
-                positive: ButtonBindings {
-                    keys: HashSet::from_iter([KeyCode::ArrowRight]),
-                    buttons: HashSet::from_iter([]),
+            movement: Axis2Bindings {
+                x: AxisBindings {
+                    positive: ButtonBindings {
+                        keys: HashSet::from_iter([KeyCode::ArrowRight]),
+                        buttons: HashSet::from_iter([]),
+                        mouse_buttons: HashSet::from_iter([]),
+                        ..Default::default()
+                    },
+                    negative: ButtonBindings {
+                        keys: HashSet::from_iter([KeyCode::ArrowLeft]),
+                        buttons: HashSet::from_iter([]),
+                        mouse_buttons: HashSet::from_iter([]),
+                        ..Default::default()
+                    },
+                    smoothing: None,
                 },
-                negative: ButtonBindings {
-                    keys: HashSet::from_iter([KeyCode::ArrowLeft]),
-                    buttons: HashSet::from_iter([]),
+                y: AxisBindings {
+                    positive: ButtonBindings {
+                        keys: HashSet::from_iter([KeyCode::ArrowUp]),
+                        buttons: HashSet::from_iter([]),
+                        mouse_buttons: HashSet::from_iter([]),
+                        ..Default::default()
+                    },
+                    negative: ButtonBindings {
+                        keys: HashSet::from_iter([KeyCode::ArrowDown]),
+                        buttons: HashSet::from_iter([]),
+                        mouse_buttons: HashSet::from_iter([]),
+                        ..Default::default()
+                    },
+                    smoothing: None,
                 },
+                deadzone: 0.0,
             },
-            y: AxisBindings {
-                positive: ButtonBindings {
-                    keys: HashSet::from_iter([KeyCode::ArrowUp]),
-                    buttons: HashSet::from_iter([]),
-                },
-                negative: ButtonBindings {
-                    keys: HashSet::from_iter([KeyCode::ArrowDown]),
-                    buttons: HashSet::from_iter([]),
-                },
+            aim: StickBindings {
+                stick: GamepadStick::Right,
             },
             jump: ButtonBindings {
                 keys: HashSet::from_iter([KeyCode::Space]),
                 buttons: HashSet::from_iter([]),
+                mouse_buttons: HashSet::from_iter([]),
+                ..Default::default()
             },
             drill: ButtonBindings {
                 keys: HashSet::from_iter([KeyCode::KeyC]),
                 buttons: HashSet::from_iter([]),
+                mouse_buttons: HashSet::from_iter([]),
+                ..Default::default()
+            },
+            special: ChordBindings {
+                parts: vec![
+                    ButtonBindings {
+                        keys: HashSet::from_iter([KeyCode::Space]),
+                        buttons: HashSet::from_iter([]),
+                        mouse_buttons: HashSet::from_iter([]),
+                        ..Default::default()
+                    },
+                    ButtonBindings {
+                        keys: HashSet::from_iter([KeyCode::KeyC]),
+                        buttons: HashSet::from_iter([]),
+                        mouse_buttons: HashSet::from_iter([]),
+                        ..Default::default()
+                    },
+                ],
             },
 
             menu_x: AxisBindings {
                 positive: ButtonBindings {
                     keys: HashSet::from_iter([KeyCode::ArrowRight]),
                     buttons: HashSet::from_iter([]),
+                    mouse_buttons: HashSet::from_iter([]),
+                    ..Default::default()
                 },
                 negative: ButtonBindings {
                     keys: HashSet::from_iter([KeyCode::ArrowLeft]),
                     buttons: HashSet::from_iter([]),
+                    mouse_buttons: HashSet::from_iter([]),
+                    ..Default::default()
                 },
+                smoothing: None,
             },
             menu_y: AxisBindings {
                 positive: ButtonBindings {
                     keys: HashSet::from_iter([KeyCode::ArrowUp]),
                     buttons: HashSet::from_iter([]),
+                    mouse_buttons: HashSet::from_iter([]),
+                    ..Default::default()
                 },
                 negative: ButtonBindings {
                     keys: HashSet::from_iter([KeyCode::ArrowDown]),
                     buttons: HashSet::from_iter([]),
+                    mouse_buttons: HashSet::from_iter([]),
+                    ..Default::default()
                 },
+                smoothing: None,
             },
             menu_accept: ButtonBindings {
                 keys: HashSet::from_iter([KeyCode::Space]),
                 buttons: HashSet::from_iter([]),
+                mouse_buttons: HashSet::from_iter([]),
+                ..Default::default()
             },
             menu_cancel: ButtonBindings {
                 keys: HashSet::from_iter([KeyCode::KeyC]),
                 buttons: HashSet::from_iter([]),
+                mouse_buttons: HashSet::from_iter([]),
+                ..Default::default()
             },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::*;
+    use crate::{
+        game::ButtonCode,
+        input::test_events::{disconnected, gamepad, key, pad_button},
+    };
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    #[test]
+    fn replay_returns_the_recorded_frames() {
+        let mut handler = InputHandler::new(&InputBindings::default());
+        handler.start_recording();
+
+        let mut recorded = Vec::new();
+        handler.event(&key(KeyCode::Space, true));
+        handler.event(&key(KeyCode::ArrowRight, true));
+        recorded.push(handler.next_state(FRAME));
+        recorded.push(handler.next_state(FRAME));
+        handler.event(&key(KeyCode::Space, false));
+        recorded.push(handler.next_state(FRAME));
+
+        let recording = handler.stop_recording().unwrap();
+        assert_eq!(recording.frames, recorded);
+        assert!(recorded[0].jump.is_pressed);
+        assert_eq!(recorded[1].jump.held_for, FRAME);
+        assert!(recorded[2].jump.is_released);
+
+        let bytes = recording.to_bytes();
+        let mut replayer = InputHandler::new(&InputBindings::default());
+        replayer.play(InputRecording::from_bytes(&bytes).unwrap());
+
+        for frame in &recorded {
+            replayer.event(&key(KeyCode::ArrowLeft, true));
+            assert_eq!(replayer.next_state(FRAME), *frame);
+        }
+
+        assert!(replayer.is_playing());
+        let live = replayer.next_state(FRAME);
+        assert!(!replayer.is_playing());
+        assert_eq!(live.movement.x.negative, Value(1.0));
+    }
+
+    #[test]
+    fn special_needs_jump_and_drill() {
+        let mut handler = InputHandler::new(&InputBindings::default());
+
+        handler.event(&key(KeyCode::Space, true));
+        assert!(!handler.next_state(FRAME).special.is_held);
+
+        handler.event(&key(KeyCode::KeyC, true));
+        let input = handler.next_state(FRAME);
+        assert!(input.special.is_pressed && input.drill.is_pressed);
+    }
+
+    #[test]
+    fn routed_handler_ignores_other_gamepads() {
+        let mut bindings = InputBindings::default();
+        bindings.jump.buttons.insert(ButtonCode::South);
+
+        let mut handler = InputHandler::new(&bindings);
+        handler.set_gamepad(Some(gamepad(1)));
+
+        handler.event(&pad_button(1, ButtonCode::South, 1.0));
+        handler.event(&pad_button(2, ButtonCode::RightStickRight, 1.0));
+        handler.event(&disconnected(2));
+
+        let input = handler.next_state(FRAME);
+        assert!(input.jump.is_held);
+        assert_eq!(input.aim, Stick(Vec2::ZERO));
+
+        handler.event(&disconnected(1));
+        assert!(!handler.next_state(FRAME).jump.is_held);
+    }
+}
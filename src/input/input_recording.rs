@@ -0,0 +1,235 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+use glam::Vec2;
+
+use crate::input::{Axis, Axis2, Button, Input, Stick, Value};
+
+/// The mapped input of consecutive frames, one `Input` per `InputHandler::next_state` call.
+///
+/// Replaying it with a fixed timestep and seeded randomness reproduces a run exactly.
+/// [`InputRecording::to_bytes`] and [`InputRecording::from_bytes`] store it in a compact
+/// binary format, e.g. to save a replay next to a bug report:
+///
+/// ```ignore
+/// std::fs::write("replay.bin", recording.to_bytes())?;
+/// let recording = InputRecording::from_bytes(&std::fs::read("replay.bin")?)?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InputRecording {
+    pub frames: Vec<Input>,
+}
+
+/// Bytes that were not written by [`InputRecording::to_bytes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputRecordingError {
+    /// The bytes don't start with the recording header, or were written by another version.
+    InvalidHeader,
+    /// The bytes end before the last frame, or continue after it.
+    InvalidLength,
+}
+
+const MAGIC: &[u8; 4] = b"DRIR";
+const VERSION: u8 = 2;
+
+impl InputRecording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[expect(dead_code, reason = "the demo has no replays")]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Encodes the recording as a header followed by every frame, in little endian.
+    #[cfg_attr(not(test), expect(dead_code, reason = "the demo doesn't save replays"))]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(self.frames.len() as u64).to_le_bytes());
+
+        for frame in &self.frames {
+            for value in [frame.movement.x, frame.movement.y] {
+                write_f32(&mut bytes, value.positive.0);
+                write_f32(&mut bytes, value.negative.0);
+            }
+            write_f32(&mut bytes, frame.aim.0.x);
+            write_f32(&mut bytes, frame.aim.0.y);
+
+            for button in [
+                frame.jump,
+                frame.drill,
+                frame.special,
+                frame.menu_x.positive,
+                frame.menu_x.negative,
+                frame.menu_y.positive,
+                frame.menu_y.negative,
+                frame.menu_accept,
+                frame.menu_cancel,
+            ] {
+                write_button(&mut bytes, button);
+            }
+        }
+
+        bytes
+    }
+
+    /// Decodes a recording written by [`InputRecording::to_bytes`].
+    #[cfg_attr(not(test), expect(dead_code, reason = "the demo doesn't load replays"))]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InputRecordingError> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC || reader.take(1)? != [VERSION] {
+            return Err(InputRecordingError::InvalidHeader);
+        }
+
+        let len = u64::from_le_bytes(reader.array()?);
+        let mut frames = Vec::new();
+        for _ in 0..len {
+            let mut movement = Axis2::<Value>::default();
+            for value in [&mut movement.x, &mut movement.y] {
+                value.positive = Value(reader.f32()?);
+                value.negative = Value(reader.f32()?);
+            }
+            let aim = Stick(Vec2::new(reader.f32()?, reader.f32()?));
+
+            frames.push(Input {
+                movement,
+                aim,
+                jump: reader.button()?,
+                drill: reader.button()?,
+                special: reader.button()?,
+                menu_x: Axis {
+                    positive: reader.button()?,
+                    negative: reader.button()?,
+                },
+                menu_y: Axis {
+                    positive: reader.button()?,
+                    negative: reader.button()?,
+                },
+                menu_accept: reader.button()?,
+                menu_cancel: reader.button()?,
+            });
+        }
+
+        if !reader.0.is_empty() {
+            return Err(InputRecordingError::InvalidLength);
+        }
+
+        Ok(Self { frames })
+    }
+}
+
+impl Display for InputRecordingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "not an input recording of version {VERSION}"),
+            Self::InvalidLength => write!(f, "input recording has the wrong length"),
+        }
+    }
+}
+
+impl Error for InputRecordingError {}
+
+fn write_f32(bytes: &mut Vec<u8>, value: f32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_button(bytes: &mut Vec<u8>, button: Button) {
+    let flags = button.is_held as u8
+        | (button.is_pressed as u8) << 1
+        | (button.is_released as u8) << 2
+        | (button.double_tapped as u8) << 3;
+
+    bytes.push(flags);
+    bytes.extend_from_slice(&(button.held_for.as_nanos() as u64).to_le_bytes());
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], InputRecordingError> {
+        let (taken, rest) = self
+            .0
+            .split_at_checked(len)
+            .ok_or(InputRecordingError::InvalidLength)?;
+        self.0 = rest;
+
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], InputRecordingError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn f32(&mut self) -> Result<f32, InputRecordingError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn button(&mut self) -> Result<Button, InputRecordingError> {
+        let [flags] = self.array()?;
+
+        Ok(Button {
+            is_held: flags & 1 != 0,
+            is_pressed: flags & 1 << 1 != 0,
+            is_released: flags & 1 << 2 != 0,
+            held_for: Duration::from_nanos(u64::from_le_bytes(self.array()?)),
+            double_tapped: flags & 1 << 3 != 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> InputRecording {
+        let mut frame = Input::default();
+        frame.movement.x.positive = Value(0.25);
+        frame.aim = Stick(Vec2::new(-0.5, 1.0));
+        frame.jump = Button {
+            is_held: true,
+            is_pressed: true,
+            is_released: false,
+            held_for: Duration::from_millis(16),
+            double_tapped: true,
+        };
+        frame.menu_y.negative.is_released = true;
+
+        InputRecording {
+            frames: vec![Input::default(), frame],
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let recording = recording();
+
+        assert_eq!(
+            InputRecording::from_bytes(&recording.to_bytes()),
+            Ok(recording)
+        );
+    }
+
+    #[test]
+    fn rejects_foreign_and_truncated_bytes() {
+        let bytes = recording().to_bytes();
+
+        assert_eq!(
+            InputRecording::from_bytes(b"not a recording"),
+            Err(InputRecordingError::InvalidHeader)
+        );
+        assert_eq!(
+            InputRecording::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(InputRecordingError::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use winit::{event::MouseButton, keyboard::KeyCode};
+
+use crate::game::{ButtonCode, GameEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputSource {
+    Key(KeyCode),
+    Button(ButtonCode),
+    MouseButton(MouseButton),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum InputDevice {
+    #[default]
+    KeyboardMouse,
+    Gamepad,
+}
+
+impl InputSource {
+    pub fn from_press(event: &GameEvent) -> Option<Self> {
+        match event {
+            GameEvent::Key {
+                code,
+                is_held: true,
+                is_repeat: false,
+            } => Some(Self::Key(*code)),
+
+            GameEvent::Button { code, value, .. } if *value >= 0.5 => Some(Self::Button(*code)),
+
+            GameEvent::MouseButton {
+                button,
+                is_held: true,
+            } => Some(Self::MouseButton(*button)),
+
+            _ => None,
+        }
+    }
+
+    pub fn device(self) -> InputDevice {
+        match self {
+            Self::Key(_) | Self::MouseButton(_) => InputDevice::KeyboardMouse,
+            Self::Button(_) => InputDevice::Gamepad,
+        }
+    }
+}
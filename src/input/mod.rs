@@ -1,10 +1,27 @@
 mod axis;
+mod axis2;
 mod button;
+mod chord;
+#[expect(clippy::module_inception)]
 mod input;
+mod input_recording;
+mod input_source;
+mod multi_input;
+mod stick;
 mod value;
 pub use axis::*;
+pub use axis2::*;
 pub use button::*;
+pub use chord::*;
 pub use input::*;
+pub use input_recording::*;
+pub use input_source::*;
+#[expect(unused_imports, reason = "the demo is single player")]
+pub use multi_input::*;
+pub use stick::*;
 pub use value::*;
 
 mod stick_handler;
+
+#[cfg(test)]
+pub(crate) mod test_events;
@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use gilrs::GamepadId;
+
+use crate::{
+    game::GameEvent,
+    input::{Input, InputBindings, InputHandler},
+};
+
+/// One `InputHandler` per player, each following the gamepad in its slot. Gamepads take the
+/// first free slot when they connect, and keyboard and mouse drive player 0.
+#[derive(Debug)]
+pub struct MultiInputHandler {
+    players: Vec<InputHandler>,
+}
+
+#[expect(dead_code, reason = "the demo is single player")]
+impl MultiInputHandler {
+    pub fn new(bindings: &InputBindings, max_players: usize) -> Self {
+        assert!(max_players > 0);
+
+        Self {
+            players: (0..max_players)
+                .map(|_| InputHandler::new(bindings))
+                .collect(),
+        }
+    }
+
+    pub fn event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::GamepadConnected(gamepad) => {
+                if self.player_of(*gamepad).is_some() {
+                    return;
+                }
+
+                if let Some(player) = self.players.iter_mut().find(|p| p.gamepad().is_none()) {
+                    player.set_gamepad(Some(*gamepad));
+                }
+            }
+
+            GameEvent::GamepadDisconnected(gamepad) => {
+                if let Some(player) = self.player_of(*gamepad) {
+                    self.players[player].event(event);
+                    self.players[player].set_gamepad(None);
+                }
+            }
+
+            GameEvent::Button { gamepad, .. } => {
+                if let Some(player) = self.player_of(*gamepad) {
+                    self.players[player].event(event);
+                }
+            }
+
+            _ => self.players[0].event(event),
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        for player in &mut self.players {
+            player.release_all();
+        }
+    }
+
+    pub fn player_of(&self, gamepad: GamepadId) -> Option<usize> {
+        self.players
+            .iter()
+            .position(|player| player.gamepad() == Some(gamepad))
+    }
+
+    pub fn player_gamepad(&self, player: usize) -> Option<GamepadId> {
+        self.players[player].gamepad()
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn next_state(&mut self, player: usize, delta: Duration) -> Input {
+        self.players[player].next_state(delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::KeyCode;
+
+    use super::*;
+    use crate::{
+        game::ButtonCode,
+        input::test_events::{connected, disconnected, gamepad, key, pad_button},
+    };
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    fn bindings() -> InputBindings {
+        let mut bindings = InputBindings::default();
+        bindings.jump.buttons.insert(ButtonCode::South);
+
+        bindings
+    }
+
+    #[test]
+    fn gamepads_take_the_first_free_slot() {
+        let mut handler = MultiInputHandler::new(&bindings(), 2);
+
+        handler.event(&connected(5));
+        handler.event(&connected(7));
+        handler.event(&connected(9));
+        assert_eq!(handler.player_of(gamepad(5)), Some(0));
+        assert_eq!(handler.player_of(gamepad(7)), Some(1));
+        assert_eq!(handler.player_of(gamepad(9)), None);
+
+        handler.event(&disconnected(5));
+        assert_eq!(handler.player_gamepad(0), None);
+
+        handler.event(&connected(9));
+        assert_eq!(handler.player_of(gamepad(9)), Some(0));
+        assert_eq!(handler.player_of(gamepad(7)), Some(1));
+    }
+
+    #[test]
+    fn buttons_go_to_their_gamepads_player() {
+        let mut handler = MultiInputHandler::new(&bindings(), 2);
+        handler.event(&connected(5));
+        handler.event(&connected(7));
+
+        handler.event(&pad_button(7, ButtonCode::South, 1.0));
+        handler.event(&pad_button(3, ButtonCode::South, 1.0));
+        assert!(!handler.next_state(0, FRAME).jump.is_held);
+        assert!(handler.next_state(1, FRAME).jump.is_held);
+
+        handler.event(&pad_button(5, ButtonCode::South, 1.0));
+        handler.event(&disconnected(7));
+        assert!(handler.next_state(0, FRAME).jump.is_held);
+        assert!(!handler.next_state(1, FRAME).jump.is_held);
+    }
+
+    #[test]
+    fn keyboard_goes_to_player_0() {
+        let mut handler = MultiInputHandler::new(&bindings(), 2);
+        handler.event(&connected(5));
+
+        handler.event(&key(KeyCode::Space, true));
+        assert!(handler.next_state(0, FRAME).jump.is_held);
+        assert!(!handler.next_state(1, FRAME).jump.is_held);
+    }
+}
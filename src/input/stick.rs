@@ -0,0 +1,32 @@
+use glam::Vec2;
+
+use crate::input::stick_handler::StickHandler;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Stick(pub Vec2);
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StickBindings {
+    pub stick: GamepadStick,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GamepadStick {
+    #[default]
+    Left,
+    Right,
+}
+
+impl Stick {
+    #[expect(dead_code, reason = "the demo has no stick bindings")]
+    pub fn value(&self) -> Vec2 {
+        self.0
+    }
+
+    pub(in crate::input) fn map(bindings: &StickBindings, stick_handler: &StickHandler) -> Self {
+        match bindings.stick {
+            GamepadStick::Left => Self(stick_handler.left_stick()),
+            GamepadStick::Right => Self(stick_handler.right_stick()),
+        }
+    }
+}
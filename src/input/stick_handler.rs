@@ -2,8 +2,11 @@ use glam::{Vec2, vec2};
 
 use crate::game::{ButtonCode, GameEvent};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct StickHandler {
+    deadzone: f32,
+    left_stick: Vec2,
+    right_stick: Vec2,
     left_stick_dir: Vec2,
     right_stick_dir: Vec2,
     left_stick_right: f32,
@@ -16,13 +19,38 @@ pub struct StickHandler {
     right_stick_down: f32,
 }
 
+impl Default for StickHandler {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.15,
+            left_stick: Vec2::ZERO,
+            right_stick: Vec2::ZERO,
+            left_stick_dir: Vec2::ZERO,
+            right_stick_dir: Vec2::ZERO,
+            left_stick_right: 0.0,
+            left_stick_left: 0.0,
+            left_stick_up: 0.0,
+            left_stick_down: 0.0,
+            right_stick_right: 0.0,
+            right_stick_left: 0.0,
+            right_stick_up: 0.0,
+            right_stick_down: 0.0,
+        }
+    }
+}
+
 impl StickHandler {
     pub fn new() -> Self {
         Self::default()
     }
 
     pub fn event(&mut self, event: &GameEvent) {
-        let GameEvent::Button { code, value } = event else {
+        if let GameEvent::GamepadDisconnected(_) = event {
+            self.release_all();
+            return;
+        }
+
+        let GameEvent::Button { code, value, .. } = event else {
             return;
         };
 
@@ -38,14 +66,44 @@ impl StickHandler {
             _ => {}
         }
 
-        self.left_stick_dir = vec2(
+        self.left_stick = self.apply_deadzone(vec2(
             self.left_stick_right - self.left_stick_left,
             self.left_stick_up - self.left_stick_down,
-        );
-        self.right_stick_dir = vec2(
+        ));
+        self.right_stick = self.apply_deadzone(vec2(
             self.right_stick_right - self.right_stick_left,
             self.right_stick_up - self.right_stick_down,
-        );
+        ));
+
+        self.left_stick_dir = self.left_stick.try_normalize().unwrap_or(Vec2::ZERO);
+        self.right_stick_dir = self.right_stick.try_normalize().unwrap_or(Vec2::ZERO);
+    }
+
+    pub fn release_all(&mut self) {
+        *self = Self {
+            deadzone: self.deadzone,
+            ..Self::default()
+        };
+    }
+
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        self.deadzone = deadzone;
+    }
+
+    fn apply_deadzone(&self, stick: Vec2) -> Vec2 {
+        if stick.length() < self.deadzone {
+            return Vec2::ZERO;
+        }
+
+        stick.clamp_length_max(1.0)
+    }
+
+    pub fn left_stick(&self) -> Vec2 {
+        self.left_stick
+    }
+
+    pub fn right_stick(&self) -> Vec2 {
+        self.right_stick
     }
 
     pub fn left_stick_dir(&self) -> Vec2 {
@@ -56,3 +114,25 @@ impl StickHandler {
         self.right_stick_dir
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::test_events::{button, disconnected};
+
+    #[test]
+    fn disconnect_zeroes_sticks() {
+        let mut handler = StickHandler::new();
+        handler.event(&button(ButtonCode::LeftStickRight, 1.0));
+        handler.event(&button(ButtonCode::RightStickUp, 1.0));
+        assert_eq!(handler.left_stick_dir(), Vec2::X);
+
+        handler.event(&disconnected(0));
+        assert_eq!(handler.left_stick_dir(), Vec2::ZERO);
+        assert_eq!(handler.right_stick_dir(), Vec2::ZERO);
+
+        // A stale accumulator would bring the old push back with the next event.
+        handler.event(&button(ButtonCode::LeftStickUp, 0.0));
+        assert_eq!(handler.left_stick(), Vec2::ZERO);
+    }
+}
@@ -0,0 +1,42 @@
+use gilrs::GamepadId;
+use serde::{
+    Deserialize,
+    de::value::{Error, SeqDeserializer},
+};
+use winit::keyboard::KeyCode;
+
+use crate::game::{ButtonCode, GameEvent};
+
+pub(crate) fn key(code: KeyCode, is_held: bool) -> GameEvent {
+    GameEvent::Key {
+        code,
+        is_held,
+        is_repeat: false,
+    }
+}
+
+/// A button event from the first gamepad.
+pub(crate) fn button(code: ButtonCode, value: f32) -> GameEvent {
+    pad_button(0, code, value)
+}
+
+pub(crate) fn pad_button(pad: usize, code: ButtonCode, value: f32) -> GameEvent {
+    GameEvent::Button {
+        gamepad: gamepad(pad),
+        code,
+        value,
+    }
+}
+
+pub(crate) fn connected(pad: usize) -> GameEvent {
+    GameEvent::GamepadConnected(gamepad(pad))
+}
+
+pub(crate) fn disconnected(pad: usize) -> GameEvent {
+    GameEvent::GamepadDisconnected(gamepad(pad))
+}
+
+/// gilrs only hands out ids of connected gamepads, so tests deserialize one instead.
+pub(crate) fn gamepad(pad: usize) -> GamepadId {
+    GamepadId::deserialize(SeqDeserializer::<_, Error>::new([pad].into_iter())).unwrap()
+}
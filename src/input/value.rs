@@ -1,20 +1,47 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
-use winit::keyboard::KeyCode;
+use winit::{event::MouseButton, keyboard::KeyCode};
 
 use crate::{
     game::{ButtonCode, GameEvent},
-    input::ButtonBindings,
+    input::{AxisSmoothing, ButtonBindings},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Value(pub f32);
 
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    #[cfg_attr(not(test), expect(dead_code, reason = "the demo reads no values"))]
+    Quadratic,
+    /// Raises values to the exponent. Exponents that aren't above 0 would map an untouched
+    /// input to 1 or infinity, so they leave values linear instead.
+    #[cfg_attr(not(test), expect(dead_code, reason = "the demo reads no values"))]
+    Power(f32),
+}
+
 #[derive(Debug, Default)]
 pub(in crate::input) struct ValueHandler {
-    key_indices: HashMap<KeyCode, u8>,
-    button_indices: HashMap<ButtonCode, u8>,
-    binding_values: [u8; 32],
+    key_indices: HashMap<KeyCode, usize>,
+    button_indices: HashMap<ButtonCode, usize>,
+    mouse_button_indices: HashMap<MouseButton, usize>,
+    binding_values: Vec<u8>,
+    response_curve: ResponseCurve,
+    smoothing: Option<AxisSmoothing>,
+    smoothed_value: f32,
+}
+
+impl ResponseCurve {
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Self::Linear => value,
+            Self::Quadratic => value * value,
+            Self::Power(exponent) if exponent > 0.0 => value.powf(exponent),
+            Self::Power(_) => value,
+        }
+    }
 }
 
 impl ValueHandler {
@@ -24,7 +51,7 @@ impl ValueHandler {
             .iter()
             .copied()
             .enumerate()
-            .map(|(index, code)| (code, index as u8))
+            .map(|(index, code)| (code, index))
             .collect();
 
         let button_indices = bindings
@@ -32,22 +59,51 @@ impl ValueHandler {
             .iter()
             .copied()
             .enumerate()
-            .map(|(index, code)| (code, index as u8))
+            .map(|(index, code)| (code, bindings.keys.len() + index))
+            .collect();
+
+        let mouse_button_indices = bindings
+            .mouse_buttons
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(index, button)| (button, bindings.keys.len() + bindings.buttons.len() + index))
             .collect();
 
         Self {
             key_indices,
             button_indices,
-            binding_values: [0; 32],
+            mouse_button_indices,
+            binding_values: vec![
+                0;
+                bindings.keys.len()
+                    + bindings.buttons.len()
+                    + bindings.mouse_buttons.len()
+            ],
+            response_curve: bindings.response_curve,
+            smoothing: None,
+            smoothed_value: 0.0,
         }
     }
 
+    pub fn with_smoothing(self, smoothing: Option<AxisSmoothing>) -> Self {
+        Self { smoothing, ..self }
+    }
+
     pub fn event(&mut self, event: &GameEvent) {
+        if let GameEvent::GamepadDisconnected(_) = event {
+            for index in self.button_indices.values() {
+                self.binding_values[*index] = 0;
+            }
+
+            return;
+        }
+
         let binding_index;
         let binding_value;
 
         match event {
-            GameEvent::Key { code, is_held } => {
+            GameEvent::Key { code, is_held, .. } => {
                 if let Some(index) = self.key_indices.get(code) {
                     binding_index = *index;
                     binding_value = if *is_held { !0 } else { 0 };
@@ -56,7 +112,7 @@ impl ValueHandler {
                 }
             }
 
-            GameEvent::Button { code, value } => {
+            GameEvent::Button { code, value, .. } => {
                 if let Some(index) = self.button_indices.get(code) {
                     binding_index = *index;
                     binding_value = (*value * 255.0) as u8;
@@ -65,19 +121,149 @@ impl ValueHandler {
                 }
             }
 
+            GameEvent::MouseButton { button, is_held } => {
+                if let Some(index) = self.mouse_button_indices.get(button) {
+                    binding_index = *index;
+                    binding_value = if *is_held { !0 } else { 0 };
+                } else {
+                    return;
+                }
+            }
+
             _ => return,
         };
 
-        self.binding_values[binding_index as usize] = binding_value;
+        self.binding_values[binding_index] = binding_value;
     }
 
-    pub fn next_state(&mut self) -> Value {
-        Value(
-            self.binding_values
-                .into_iter()
-                .map(|x| x as f32 / 255.0)
-                .sum::<f32>()
-                .min(1.0),
-        )
+    pub fn release_all(&mut self) {
+        self.binding_values.fill(0);
+    }
+
+    /// `delta` is the time since the previous call, which smoothing moves the value by.
+    pub fn next_state(&mut self, delta: Duration) -> Value {
+        let value = self
+            .binding_values
+            .iter()
+            .map(|x| *x as f32 / 255.0)
+            .sum::<f32>();
+
+        let value = self.response_curve.apply(value).min(1.0);
+
+        let Some(smoothing) = self.smoothing else {
+            return Value(value);
+        };
+
+        let dt = delta.as_secs_f32();
+
+        let is_analog = self
+            .button_indices
+            .values()
+            .any(|index| self.binding_values[*index] != 0);
+
+        self.smoothed_value = if is_analog {
+            value
+        } else if value > self.smoothed_value {
+            (self.smoothed_value + smoothing.attack * dt).min(value)
+        } else {
+            (self.smoothed_value - smoothing.decay * dt).max(value)
+        };
+
+        Value(self.smoothed_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::input::test_events::{button, key};
+
+    #[test]
+    fn two_held_keys_clamp_to_one() {
+        let mut handler = ValueHandler::new(&ButtonBindings {
+            keys: HashSet::from_iter([KeyCode::KeyD, KeyCode::ArrowRight]),
+            ..Default::default()
+        });
+
+        handler.event(&key(KeyCode::KeyD, true));
+        handler.event(&key(KeyCode::ArrowRight, true));
+
+        assert_eq!(handler.next_state(Duration::ZERO), Value(1.0));
+    }
+
+    #[test]
+    fn half_pulled_trigger_is_half() {
+        let mut handler = ValueHandler::new(&ButtonBindings {
+            buttons: HashSet::from_iter([ButtonCode::RightTrigger2]),
+            ..Default::default()
+        });
+
+        handler.event(&button(ButtonCode::RightTrigger2, 0.5));
+
+        let Value(value) = handler.next_state(Duration::ZERO);
+        assert!((value - 0.5).abs() < 0.01, "{value}");
+    }
+
+    #[test]
+    fn quadratic_curve_halves_a_half_value() {
+        assert_eq!(ResponseCurve::Linear.apply(0.5), 0.5);
+        assert_eq!(ResponseCurve::Quadratic.apply(0.5), 0.25);
+
+        let mut handler = ValueHandler::new(&ButtonBindings {
+            buttons: HashSet::from_iter([ButtonCode::RightTrigger2]),
+            response_curve: ResponseCurve::Quadratic,
+            ..Default::default()
+        });
+
+        handler.event(&button(ButtonCode::RightTrigger2, 0.5));
+
+        let Value(value) = handler.next_state(Duration::ZERO);
+        assert!((value - 0.25).abs() < 0.01, "{value}");
+    }
+
+    #[test]
+    fn smoothing_ramps_up_keys_but_not_gamepads() {
+        let bindings = ButtonBindings {
+            keys: HashSet::from_iter([KeyCode::KeyD]),
+            buttons: HashSet::from_iter([ButtonCode::RightTrigger2]),
+            ..Default::default()
+        };
+        let smoothing = AxisSmoothing {
+            attack: 4.0,
+            decay: 8.0,
+        };
+        let frame = Duration::from_millis(100);
+
+        let mut raw = ValueHandler::new(&bindings);
+        let mut smoothed = ValueHandler::new(&bindings).with_smoothing(Some(smoothing));
+
+        raw.event(&key(KeyCode::KeyD, true));
+        smoothed.event(&key(KeyCode::KeyD, true));
+
+        for expected in [0.4, 0.8, 1.0] {
+            assert_eq!(raw.next_state(frame), Value(1.0));
+
+            let Value(value) = smoothed.next_state(frame);
+            assert!((value - expected).abs() < 1e-5, "{value} != {expected}");
+        }
+
+        smoothed.event(&key(KeyCode::KeyD, false));
+        smoothed.event(&button(ButtonCode::RightTrigger2, 0.5));
+
+        let Value(value) = smoothed.next_state(frame);
+        assert!((value - 0.5).abs() < 0.01, "{value}");
+    }
+
+    #[test]
+    fn power_curve_keeps_zero_at_zero() {
+        for exponent in [2.0, 0.5, 0.0, -1.0, f32::NAN] {
+            let curve = ResponseCurve::Power(exponent);
+
+            assert_eq!(curve.apply(0.0), 0.0, "exponent {exponent}");
+            assert!(curve.apply(0.5) <= 1.0, "exponent {exponent}");
+            assert_eq!(curve.apply(1.0), 1.0, "exponent {exponent}");
+        }
     }
 }
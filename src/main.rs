@@ -2,7 +2,11 @@ mod asset;
 mod game;
 mod input;
 mod renderer;
+mod trace;
 
 fn main() {
-    game::run();
+    if let Err(err) = game::run() {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,88 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use glam::{UVec2, uvec2};
+
+use crate::renderer::{AtlasId, Sprite};
+
+/// An atlas with its sprites named by their pixel rects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Atlas {
+    pub id: AtlasId,
+    pub size: UVec2,
+    regions: HashMap<String, (UVec2, UVec2)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AtlasRegionsError {
+    pub line: usize,
+}
+
+impl Atlas {
+    pub fn new(id: AtlasId, size: UVec2) -> Self {
+        Self {
+            id,
+            size,
+            regions: HashMap::new(),
+        }
+    }
+
+    /// Names the pixels from `min` up to, but not including, `max`.
+    pub fn insert(&mut self, name: impl Into<String>, min: UVec2, max: UVec2) {
+        self.regions.insert(name.into(), (min, max));
+    }
+
+    pub fn sprite(&self, name: &str) -> Option<Sprite> {
+        let (min, max) = self.regions.get(name)?;
+
+        Some(Sprite::region(*min, *max, self.size))
+    }
+
+    /// Adds the regions of a sidecar file with one `name x y width height` region per line.
+    /// Empty lines and lines starting with `#` are skipped.
+    pub fn parse_regions(&mut self, text: &str) -> Result<(), AtlasRegionsError> {
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = AtlasRegionsError { line: index + 1 };
+
+            let mut words = line.split_whitespace();
+            let name = words.next().ok_or(error.clone())?;
+
+            let mut numbers = [0; 4];
+            for number in &mut numbers {
+                *number = words
+                    .next()
+                    .and_then(|word| word.parse().ok())
+                    .ok_or(error.clone())?;
+            }
+
+            if words.next().is_some() {
+                return Err(error);
+            }
+
+            let [x, y, width, height] = numbers;
+            self.insert(name, uvec2(x, y), uvec2(x + width, y + height));
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for AtlasRegionsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} is not a `name x y width height` region",
+            self.line
+        )
+    }
+}
+
+impl Error for AtlasRegionsError {}
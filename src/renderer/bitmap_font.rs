@@ -0,0 +1,68 @@
+use std::num::NonZeroU32;
+
+use glam::{U8Vec4, UVec2, Vec2, uvec2, vec2};
+
+use crate::renderer::{AtlasId, PIXELS_PER_UNIT, Quad, RenderLayer, Sprite};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitmapFont {
+    pub atlas: AtlasId,
+    pub atlas_size: UVec2,
+    pub grid_origin: UVec2,
+    pub glyph_size: UVec2,
+    /// Glyphs per row of the grid, which can't be 0 since glyphs are laid out by dividing by it.
+    pub columns: NonZeroU32,
+    pub first_char: u8,
+    pub last_char: u8,
+}
+
+impl BitmapFont {
+    pub fn glyph(&self, c: char) -> Option<Sprite> {
+        if !c.is_ascii() || (c as u8) < self.first_char || (c as u8) > self.last_char {
+            return None;
+        }
+
+        let index = (c as u8 - self.first_char) as u32;
+        let cell = uvec2(index % self.columns, index / self.columns);
+        let glyph_min = self.grid_origin + cell * self.glyph_size;
+
+        let atlas_size = self.atlas_size.as_vec2();
+        let glyph_size = self.glyph_size.as_vec2();
+
+        Some(Sprite {
+            center: (glyph_min.as_vec2() + glyph_size / 2.0) / atlas_size,
+            extents: glyph_size / 2.0 / atlas_size,
+        })
+    }
+
+    pub fn glyph_world_size(&self, scale: f32) -> Vec2 {
+        self.glyph_size.as_vec2() / PIXELS_PER_UNIT * scale
+    }
+}
+
+impl<'a> RenderLayer<'a> {
+    pub fn render_text(&mut self, font: &BitmapFont, text: &str, origin: Vec2, scale: f32) {
+        let glyph_size = font.glyph_world_size(scale);
+        let mut cursor = origin;
+
+        for c in text.chars() {
+            if c == '\n' {
+                cursor = vec2(origin.x, cursor.y - glyph_size.y);
+                continue;
+            }
+
+            if let Some(sprite) = font.glyph(c) {
+                self.render_quad(Quad {
+                    center: cursor + glyph_size * vec2(0.5, -0.5),
+                    sprite,
+                    layer: 0.0,
+                    atlas: font.atlas,
+                    scale: Vec2::splat(scale),
+                    tint: U8Vec4::MAX,
+                });
+            }
+
+            cursor.x += glyph_size.x;
+        }
+    }
+}
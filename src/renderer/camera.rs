@@ -0,0 +1,192 @@
+use glam::{Vec2, vec2};
+
+use crate::renderer::{MAX_ORTHO_SIZE, PIXELS_PER_UNIT, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub center: Vec2,
+    pub ortho_size: f32,
+    pub rotation: f32,
+    /// Snaps the camera center to the pixel grid, on by default.
+    ///
+    /// Quad centers are always snapped, so with a snapped camera sprites stay pixel aligned but
+    /// a slowly moving camera steps one pixel at a time. Turning this off scrolls smoothly, at
+    /// the cost of sprites landing between pixels and shimmering as the camera moves.
+    pub pixel_snap: bool,
+}
+
+impl Camera {
+    pub fn new(center: Vec2) -> Self {
+        Self {
+            center,
+            ortho_size: MAX_ORTHO_SIZE,
+            rotation: 0.0,
+            pixel_snap: true,
+        }
+    }
+
+    pub fn set_zoom(&mut self, ortho_size: f32) -> bool {
+        self.ortho_size = clamp_ortho_size(ortho_size);
+        self.ortho_size != ortho_size
+    }
+
+    /// `aspect` is the renderer's, from `Renderer::aspect`.
+    pub fn zoom_to_fit(&mut self, world_rect: Rect, aspect: f32) -> bool {
+        let extents = world_rect.extents();
+
+        self.center = world_rect.center();
+        self.set_zoom(extents.y.max(extents.x / aspect))
+    }
+
+    /// The world-space bounding box of what this camera shows on a full-screen layer.
+    ///
+    /// The render texture is letterboxed into the output, so the visible area always has the
+    /// renderer's `aspect` no matter the window's size.
+    pub fn visible_rect(&self, aspect: f32) -> Rect {
+        let rotation = Vec2::from_angle(-self.rotation);
+        let center = rotation.rotate(self.center);
+        let center = if self.pixel_snap {
+            (center * PIXELS_PER_UNIT).floor() / PIXELS_PER_UNIT
+        } else {
+            center
+        };
+
+        rotated_view_rect(
+            center,
+            rotation,
+            vec2(aspect, 1.0) * clamp_ortho_size(self.ortho_size),
+        )
+    }
+
+    /// Moves the camera so its visible rect stays inside `world_bounds`. On an axis where the
+    /// bounds are smaller than the view, the camera centers on them instead.
+    pub fn clamped_to(&self, world_bounds: Rect, aspect: f32) -> Camera {
+        let view_extents = self.visible_rect(aspect).extents();
+        let bounds_center = world_bounds.center();
+        let bounds_extents = world_bounds.extents();
+
+        let min = bounds_center - (bounds_extents - view_extents).max(Vec2::ZERO);
+        let max = bounds_center + (bounds_extents - view_extents).max(Vec2::ZERO);
+
+        Camera {
+            center: self.center.clamp(min, max),
+            ..*self
+        }
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new(Vec2::ZERO)
+    }
+}
+
+/// Bounds a view given in camera space, where `center` is already rotated by `rotation`.
+pub(in crate::renderer) fn rotated_view_rect(
+    center: Vec2,
+    rotation: Vec2,
+    view_extents: Vec2,
+) -> Rect {
+    let center = vec2(rotation.x, -rotation.y).rotate(center);
+    let extents = vec2(
+        rotation.x.abs() * view_extents.x + rotation.y.abs() * view_extents.y,
+        rotation.y.abs() * view_extents.x + rotation.x.abs() * view_extents.y,
+    );
+
+    Rect::from_center_extents(center, extents)
+}
+
+pub(in crate::renderer) fn clamp_ortho_size(ortho_size: f32) -> f32 {
+    if ortho_size.is_nan() {
+        return MAX_ORTHO_SIZE;
+    }
+
+    ortho_size.clamp(1.0 / PIXELS_PER_UNIT, MAX_ORTHO_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASPECT: f32 = 16.0 / 9.0;
+
+    fn unsnapped(center: Vec2) -> Camera {
+        Camera {
+            pixel_snap: false,
+            ..Camera::new(center)
+        }
+    }
+
+    #[test]
+    fn visible_rect_has_the_renderer_aspect() {
+        for aspect in [ASPECT, 4.0 / 3.0, 1.0] {
+            let rect = unsnapped(vec2(2.0, -3.0)).visible_rect(aspect);
+            let size = rect.size();
+
+            assert!(
+                (size.x / size.y - aspect).abs() < 1e-5,
+                "{size} at {aspect}"
+            );
+            assert_eq!(size.y, 2.0 * MAX_ORTHO_SIZE);
+            assert_eq!(rect.center(), vec2(2.0, -3.0));
+        }
+    }
+
+    #[test]
+    fn clamped_to_keeps_the_view_inside_the_bounds() {
+        let bounds = Rect {
+            min: vec2(0.0, 0.0),
+            max: vec2(100.0, 50.0),
+        };
+
+        let camera = unsnapped(vec2(-5.0, 60.0)).clamped_to(bounds, ASPECT);
+        let rect = camera.visible_rect(ASPECT);
+
+        assert!((rect.min.x - bounds.min.x).abs() < 1e-4, "{rect:?}");
+        assert!((rect.max.y - bounds.max.y).abs() < 1e-4, "{rect:?}");
+
+        let inside = unsnapped(vec2(50.0, 25.0));
+        assert_eq!(inside.clamped_to(bounds, ASPECT), inside);
+    }
+
+    #[test]
+    fn clamped_to_centers_on_bounds_smaller_than_the_view() {
+        let small = Rect {
+            min: vec2(10.0, 10.0),
+            max: vec2(14.0, 12.0),
+        };
+        let camera = unsnapped(vec2(-30.0, 40.0)).clamped_to(small, ASPECT);
+        assert_eq!(camera.center, small.center());
+
+        // Wide enough to scroll along, but shorter than the view.
+        let corridor = Rect {
+            min: vec2(0.0, 0.0),
+            max: vec2(100.0, 4.0),
+        };
+        let camera = unsnapped(vec2(50.0, 40.0)).clamped_to(corridor, ASPECT);
+        assert_eq!(camera.center, vec2(50.0, 2.0));
+    }
+
+    #[test]
+    fn zoom_to_fit_shows_the_whole_rect() {
+        let mut camera = unsnapped(Vec2::ZERO);
+
+        for extents in [vec2(10.0, 2.0), vec2(1.0, 8.0)] {
+            let rect = Rect::from_center_extents(vec2(3.0, 4.0), extents);
+
+            assert!(!camera.zoom_to_fit(rect, ASPECT));
+            assert_eq!(camera.center, rect.center());
+
+            let visible = camera.visible_rect(ASPECT);
+            assert!(visible.extents().cmpge(extents - 1e-4).all(), "{visible:?}");
+            assert!(
+                (visible.extents() - extents).abs().min_element() < 1e-4,
+                "{visible:?}"
+            );
+        }
+
+        let huge = Rect::from_center_extents(Vec2::ZERO, Vec2::splat(100.0));
+        assert!(camera.zoom_to_fit(huge, ASPECT));
+        assert_eq!(camera.ortho_size, MAX_ORTHO_SIZE);
+    }
+}
@@ -0,0 +1,78 @@
+use glam::Vec2;
+
+/// Eases a camera center toward a target.
+///
+/// Pass the result as the camera's center unsnapped; the layer snaps it to pixels afterwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraFollow {
+    pub center: Vec2,
+    /// How fast the center catches up, as the fraction `1 - e^-stiffness` of the remaining
+    /// distance covered per second. Higher is snappier.
+    pub stiffness: f32,
+    /// Half size of a box around the center the target can move in without the camera
+    /// following.
+    pub dead_zone: Vec2,
+}
+
+impl CameraFollow {
+    pub fn new(center: Vec2) -> Self {
+        Self {
+            center,
+            stiffness: 8.0,
+            dead_zone: Vec2::ZERO,
+        }
+    }
+
+    pub fn update(&mut self, target: Vec2, dt: f32) -> Vec2 {
+        let offset = target - self.center;
+        let goal = target - offset.clamp(-self.dead_zone, self.dead_zone);
+
+        self.center = goal + (self.center - goal) * (-self.stiffness * dt).exp();
+
+        self.center
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::vec2;
+
+    use super::*;
+
+    fn follow_for_a_second(fps: u32) -> Vec2 {
+        let mut follow = CameraFollow::new(Vec2::ZERO);
+        for _ in 0..fps {
+            follow.update(vec2(10.0, -4.0), 1.0 / fps as f32);
+        }
+
+        follow.center
+    }
+
+    #[test]
+    fn smoothing_is_frame_rate_independent() {
+        let at_30 = follow_for_a_second(30);
+        let at_144 = follow_for_a_second(144);
+
+        assert!(at_30.abs_diff_eq(at_144, 1e-4), "{at_30} != {at_144}");
+        assert!(at_30.distance(vec2(10.0, -4.0)) < 0.01, "{at_30}");
+    }
+
+    #[test]
+    fn dead_zone_holds_the_camera() {
+        let mut follow = CameraFollow {
+            dead_zone: vec2(2.0, 1.0),
+            ..CameraFollow::new(Vec2::ZERO)
+        };
+
+        assert_eq!(follow.update(vec2(1.5, -0.5), 0.1), Vec2::ZERO);
+
+        for _ in 0..100 {
+            follow.update(vec2(5.0, 0.0), 0.1);
+        }
+        assert!(
+            follow.center.abs_diff_eq(vec2(3.0, 0.0), 1e-4),
+            "{}",
+            follow.center
+        );
+    }
+}
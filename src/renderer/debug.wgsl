@@ -0,0 +1,24 @@
+struct Vertex {
+    @location(0) pos: vec2f,
+    @location(1) color: vec4f,
+}
+
+struct Fragment {
+    @builtin(position) pos: vec4f,
+    @location(0) color: vec4f,
+}
+
+@vertex
+fn vs_main(input: Vertex) -> Fragment {
+    var output: Fragment;
+
+    output.pos = vec4f(input.pos, 0.0, 1.0);
+    output.color = input.color;
+
+    return output;
+}
+
+@fragment
+fn fs_main(input: Fragment) -> @location(0) vec4f {
+    return input.color;
+}
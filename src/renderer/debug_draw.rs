@@ -0,0 +1,68 @@
+use bytemuck::NoUninit;
+use glam::{U8Vec4, Vec2, Vec4, vec2};
+
+use crate::renderer::{Rect, RenderLayer, RenderStats, RenderUniform, color_to_tint};
+
+pub struct DebugDraw<'a> {
+    vertices: &'a mut Vec<DebugVertex>,
+    uniform: RenderUniform,
+    viewport: Rect,
+    stats: &'a mut RenderStats,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, NoUninit)]
+pub(in crate::renderer) struct DebugVertex {
+    pub pos: Vec2,
+    pub color: U8Vec4,
+}
+
+impl<'a> RenderLayer<'a> {
+    pub fn debug_draw(&mut self) -> DebugDraw<'_> {
+        DebugDraw {
+            vertices: &mut self.renderer.debug_vertex_vec,
+            uniform: self.uniform,
+            viewport: self.viewport,
+            stats: self.stats,
+        }
+    }
+}
+
+impl<'a> DebugDraw<'a> {
+    pub fn line(&mut self, a: Vec2, b: Vec2, color: Vec4) {
+        let color = color_to_tint(color);
+
+        self.vertices.push(DebugVertex {
+            pos: self.to_screen(a),
+            color,
+        });
+        self.vertices.push(DebugVertex {
+            pos: self.to_screen(b),
+            color,
+        });
+
+        self.stats.debug_lines += 1;
+    }
+
+    pub fn rect_outline(&mut self, rect: Rect, color: Vec4) {
+        let corners = [
+            rect.min,
+            vec2(rect.max.x, rect.min.y),
+            rect.max,
+            vec2(rect.min.x, rect.max.y),
+        ];
+
+        for i in 0..corners.len() {
+            self.line(corners[i], corners[(i + 1) % corners.len()], color);
+        }
+    }
+
+    fn to_screen(&self, world_pos: Vec2) -> Vec2 {
+        let cam_pos = self.uniform.cam_rotation.rotate(world_pos) - self.uniform.cam_center;
+
+        let view_pos = cam_pos / self.uniform.cam_ortho_size / vec2(self.uniform.cam_aspect, 1.0);
+
+        let viewport_center = (self.viewport.center() * 2.0 - 1.0) * vec2(1.0, -1.0);
+        viewport_center + view_pos * self.viewport.size()
+    }
+}
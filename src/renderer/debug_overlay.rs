@@ -0,0 +1,179 @@
+use std::{fmt::Write, num::NonZeroU32, time::Duration};
+
+use glam::{UVec2, uvec2, vec2};
+use image::{Rgba, RgbaImage};
+
+use crate::renderer::{BitmapFont, Camera, RenderContext, RenderFrame, Renderer};
+
+const FRAME_SAMPLES: usize = 60;
+
+const DEBUG_FONT_COLUMNS: u32 = 16;
+const DEBUG_FONT_FIRST_CHAR: u8 = b' ';
+const DEBUG_FONT_LAST_CHAR: u8 = b'Z';
+/// Glyph cells are one pixel wider and taller than the glyphs, which spaces the text out.
+const DEBUG_FONT_CELL: UVec2 = uvec2(4, 6);
+
+/// 3x5 glyphs for the characters the overlay prints, top row first and leftmost pixel in the
+/// highest bit. Every other character is blank.
+const DEBUG_GLYPHS: [(char, [u8; 5]); 15] = [
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('P', [0b111, 0b101, 0b111, 0b100, 0b100]),
+    ('S', [0b111, 0b100, 0b111, 0b001, 0b111]),
+];
+
+/// Shows the FPS and frame time averaged over the last 60 frames in the top left corner.
+#[derive(Debug, Clone)]
+pub struct DebugOverlay {
+    pub font: BitmapFont,
+    pub is_enabled: bool,
+    frame_times: [f32; FRAME_SAMPLES],
+    frame_index: usize,
+    frame_count: usize,
+    text: String,
+}
+
+impl DebugOverlay {
+    pub fn new(font: BitmapFont) -> Self {
+        Self {
+            font,
+            is_enabled: true,
+            frame_times: [0.0; FRAME_SAMPLES],
+            frame_index: 0,
+            frame_count: 0,
+            text: String::new(),
+        }
+    }
+
+    /// Records one frame's delta, as given by `GameContext::delta` in `Game::update`.
+    pub fn record(&mut self, delta: Duration) {
+        self.frame_times[self.frame_index] = delta.as_secs_f32();
+        self.frame_index = (self.frame_index + 1) % FRAME_SAMPLES;
+        self.frame_count = (self.frame_count + 1).min(FRAME_SAMPLES);
+
+        let frame_time = self.frame_time();
+
+        self.text.clear();
+        let _ = write!(
+            self.text,
+            "FPS {:.0}\n{:.2} MS",
+            1.0 / frame_time.max(f32::EPSILON),
+            frame_time * 1000.0,
+        );
+    }
+
+    /// The average frame time in seconds.
+    pub fn frame_time(&self) -> f32 {
+        if self.frame_count == 0 {
+            return 0.0;
+        }
+
+        self.frame_times[..self.frame_count].iter().sum::<f32>() / self.frame_count as f32
+    }
+}
+
+impl Renderer {
+    /// Loads a tiny built-in font that covers the overlay's text, for games without a font of
+    /// their own.
+    pub fn load_debug_font(&mut self, ctx: RenderContext) -> BitmapFont {
+        let image = debug_font_image();
+
+        BitmapFont {
+            atlas: self.load_atlas(&image, ctx),
+            atlas_size: uvec2(image.width(), image.height()),
+            grid_origin: UVec2::ZERO,
+            glyph_size: DEBUG_FONT_CELL,
+            columns: NonZeroU32::new(DEBUG_FONT_COLUMNS).unwrap(),
+            first_char: DEBUG_FONT_FIRST_CHAR,
+            last_char: DEBUG_FONT_LAST_CHAR,
+        }
+    }
+}
+
+fn debug_font_image() -> RgbaImage {
+    let glyph_count = (DEBUG_FONT_LAST_CHAR - DEBUG_FONT_FIRST_CHAR) as u32 + 1;
+    let size =
+        DEBUG_FONT_CELL * uvec2(DEBUG_FONT_COLUMNS, glyph_count.div_ceil(DEBUG_FONT_COLUMNS));
+    let mut image = RgbaImage::new(size.x, size.y);
+
+    for (c, rows) in DEBUG_GLYPHS {
+        let index = (c as u8 - DEBUG_FONT_FIRST_CHAR) as u32;
+        let cell_min =
+            uvec2(index % DEBUG_FONT_COLUMNS, index / DEBUG_FONT_COLUMNS) * DEBUG_FONT_CELL;
+
+        for (y, row) in rows.into_iter().enumerate() {
+            for x in 0..3 {
+                if row & (0b100 >> x) != 0 {
+                    image.put_pixel(cell_min.x + x, cell_min.y + y as u32, Rgba([255; 4]));
+                }
+            }
+        }
+    }
+
+    image
+}
+
+impl<'a> RenderFrame<'a> {
+    /// Draws the overlay in its own layer. Call it last so it ends up on top of the game.
+    pub fn render_debug_overlay(&mut self, overlay: &DebugOverlay) {
+        if !overlay.is_enabled {
+            return;
+        }
+
+        self.render_layer(
+            |r| {
+                let rect = r.visible_rect();
+
+                r.render_text(
+                    &overlay.font,
+                    &overlay.text,
+                    vec2(rect.min.x, rect.max.y),
+                    1.0,
+                );
+            },
+            Camera::default(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_font_covers_the_overlay_text() {
+        let image = debug_font_image();
+        let font = BitmapFont {
+            atlas: Default::default(),
+            atlas_size: uvec2(image.width(), image.height()),
+            grid_origin: UVec2::ZERO,
+            glyph_size: DEBUG_FONT_CELL,
+            columns: NonZeroU32::new(DEBUG_FONT_COLUMNS).unwrap(),
+            first_char: DEBUG_FONT_FIRST_CHAR,
+            last_char: DEBUG_FONT_LAST_CHAR,
+        };
+
+        let mut overlay = DebugOverlay::new(font);
+        overlay.record(Duration::from_secs_f32(1.0 / 60.0));
+
+        for c in overlay.text.chars().filter(|c| !c.is_whitespace()) {
+            let sprite = font.glyph(c).unwrap();
+            let min = ((sprite.center - sprite.extents) * font.atlas_size.as_vec2()).as_uvec2();
+            let is_drawn =
+                (0..3).any(|x| (0..5).any(|y| image.get_pixel(min.x + x, min.y + y).0[3] != 0));
+
+            assert!(is_drawn, "{c:?} has no glyph");
+        }
+    }
+}
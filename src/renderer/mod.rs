@@ -1,16 +1,40 @@
 #![expect(unused_imports)]
 #![expect(dead_code)]
 
+mod atlas;
+mod bitmap_font;
+mod camera;
+mod camera_follow;
+mod debug_draw;
+mod debug_overlay;
+mod particle_emitter;
+mod rect;
 mod render_buffer;
 mod render_frame;
 mod render_layer;
+mod render_target;
+#[expect(clippy::module_inception)]
 mod renderer;
+mod screen_shake;
+mod sprite_animation;
+mod tilemap;
+pub use atlas::*;
+pub use bitmap_font::*;
+pub use camera::*;
+pub use camera_follow::*;
+pub use debug_draw::*;
+pub use debug_overlay::*;
+pub use particle_emitter::*;
+pub use rect::*;
 pub use render_buffer::*;
 pub use render_frame::*;
 pub use render_layer::*;
+pub use render_target::*;
 pub use renderer::*;
+pub use screen_shake::*;
+pub use sprite_animation::*;
+pub use tilemap::*;
 
 const PIXELS_PER_UNIT: f32 = 16.0;
-const ASPECT: f32 = 16.0 / 9.0;
-const ORTHO_SIZE: f32 = 10.0;
+const MAX_ORTHO_SIZE: f32 = 10.0;
 const DYN_QUAD_CAP: usize = 1024;
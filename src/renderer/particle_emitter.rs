@@ -0,0 +1,90 @@
+use glam::{Vec2, Vec4};
+
+use crate::renderer::{AtlasId, Quad, RenderLayer, Sprite, color_to_tint};
+
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    pub position: Vec2,
+    pub spawn_rate: f32,
+    pub lifetime: f32,
+    pub velocity: Vec2,
+    pub gravity: Vec2,
+    pub start_tint: Vec4,
+    pub end_tint: Vec4,
+    pub start_size: f32,
+    pub end_size: f32,
+    pub sprite: Sprite,
+    pub atlas: AtlasId,
+    particles: Vec<Particle>,
+    spawn_lag: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    age: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(sprite: Sprite, atlas: AtlasId) -> Self {
+        Self {
+            position: Vec2::ZERO,
+            spawn_rate: 10.0,
+            lifetime: 1.0,
+            velocity: Vec2::ZERO,
+            gravity: Vec2::ZERO,
+            start_tint: Vec4::ONE,
+            end_tint: Vec4::ONE,
+            start_size: 1.0,
+            end_size: 1.0,
+            sprite,
+            atlas,
+            particles: Vec::new(),
+            spawn_lag: 0.0,
+        }
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.age += dt;
+            particle.velocity += self.gravity * dt;
+            particle.position += particle.velocity * dt;
+        }
+
+        self.particles
+            .retain(|particle| particle.age < self.lifetime);
+
+        self.spawn_lag += dt * self.spawn_rate;
+
+        while self.spawn_lag >= 1.0 {
+            self.spawn_lag -= 1.0;
+
+            self.particles.push(Particle {
+                position: self.position,
+                velocity: self.velocity,
+                age: 0.0,
+            });
+        }
+    }
+
+    /// Pushes every live particle into the layer's dynamic quad batch.
+    pub fn emit_to(&self, r: &mut RenderLayer) {
+        for particle in &self.particles {
+            let t = (particle.age / self.lifetime).clamp(0.0, 1.0);
+
+            r.render_quad(Quad {
+                center: particle.position,
+                sprite: self.sprite,
+                layer: 0.0,
+                atlas: self.atlas,
+                scale: Vec2::splat(self.start_size + (self.end_size - self.start_size) * t),
+                tint: color_to_tint(self.start_tint.lerp(self.end_tint, t)),
+            });
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use glam::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    pub fn from_center_extents(center: Vec2, extents: Vec2) -> Self {
+        Self {
+            min: center - extents,
+            max: center + extents,
+        }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn extents(&self) -> Vec2 {
+        (self.max - self.min) / 2.0
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+}
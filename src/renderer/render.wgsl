@@ -1,6 +1,4 @@
 const PIXELS_PER_UNIT: f32 = 16.0;
-const ASPECT: f32 = 16.0 / 9.0;
-const ORTHO_SIZE: f32 = 10.0;
 
 struct Vertex {
     @location(0) vertex_pos: vec2f,
@@ -8,15 +6,21 @@ struct Vertex {
     @location(2) quad_sprite_center: vec2f,
     @location(3) quad_sprite_extents: vec2f,
     @location(4) quad_layer: f32,
+    @location(5) quad_scale: vec2f,
+    @location(6) quad_tint: vec4f,
 }
 
 struct Fragment {
     @builtin(position) pos: vec4f,
     @location(0) uv: vec2f,
+    @location(1) tint: vec4f,
 }
 
 struct Uniform {
     cam_center: vec2f,
+    cam_rotation: vec2f,
+    cam_ortho_size: f32,
+    cam_aspect: f32,
 }
 
 @group(0) @binding(0) var<uniform> u: Uniform;
@@ -29,19 +33,29 @@ fn vs_main(input: Vertex) -> Fragment {
 
     let quad_extents = input.quad_sprite_extents
         * vec2f(textureDimensions(sprites))
-        / PIXELS_PER_UNIT;
+        / PIXELS_PER_UNIT
+        * input.quad_scale;
 
     let quad_center = round(input.quad_center * PIXELS_PER_UNIT) / PIXELS_PER_UNIT;
     let world_pos = quad_center + input.vertex_pos * quad_extents;
-    let screen_pos = (world_pos - u.cam_center) / ORTHO_SIZE / vec2f(ASPECT, 1.0);
+    let cam_pos = rotate(world_pos, u.cam_rotation) - u.cam_center;
+    let screen_pos = cam_pos / u.cam_ortho_size / vec2f(u.cam_aspect, 1.0);
 
     output.pos = vec4f(screen_pos, input.quad_layer / 1000.0, 1.0);
     output.uv = input.quad_sprite_center + input.vertex_pos * input.quad_sprite_extents;
+    output.tint = input.quad_tint;
     
     return output;
 }
 
+fn rotate(v: vec2f, rotation: vec2f) -> vec2f {
+    return vec2f(
+        v.x * rotation.x - v.y * rotation.y,
+        v.x * rotation.y + v.y * rotation.x,
+    );
+}
+
 @fragment
 fn fs_main(input: Fragment) -> @location(0) vec4f {
-    return textureSample(sprites, sprites_sampler, input.uv);
+    return textureSample(sprites, sprites_sampler, input.uv) * input.tint;
 }
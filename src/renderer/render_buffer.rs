@@ -1,6 +1,10 @@
-use std::ops::{Bound, RangeBounds};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    ops::{Bound, Range, RangeBounds},
+};
 
-use bytemuck::bytes_of;
+use bytemuck::{bytes_of, cast_slice};
 use wgpu::{Buffer, BufferDescriptor, BufferUsages};
 
 use crate::renderer::{Quad, RenderContext};
@@ -20,6 +24,14 @@ pub struct RenderBufferRef<'a> {
     index: u64,
 }
 
+/// A quad index or range that does not fit the buffer it was used on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RenderBufferBoundsError {
+    pub start: usize,
+    pub end: usize,
+    pub len: usize,
+}
+
 impl RenderBuffer {
     pub fn new_uninit(cap: usize, ctx: RenderContext) -> Self {
         Self(ctx.device.create_buffer(&BufferDescriptor {
@@ -30,6 +42,10 @@ impl RenderBuffer {
         }))
     }
 
+    /// Creates a buffer that keeps its quads on the GPU until it is written again or dropped.
+    ///
+    /// Writes are queued before the next submitted frame, so a buffer should not be rewritten
+    /// between draws of the same frame.
     pub fn new(quads: &[Quad], ctx: RenderContext) -> Self {
         let result = Self::new_uninit(quads.len(), ctx);
         result.write(quads, ctx);
@@ -42,35 +58,33 @@ impl RenderBuffer {
     }
 
     pub fn slice(&self, range: impl RangeBounds<usize>) -> RenderBufferSlice<'_> {
-        let start = match range.start_bound() {
-            Bound::Included(start) => *start as u64,
-            Bound::Excluded(start) => *start as u64 + 1,
-            Bound::Unbounded => 0,
-        };
-
-        let end = match range.start_bound() {
-            Bound::Included(end) => *end as u64 + 1,
-            Bound::Excluded(end) => *end as u64,
-            Bound::Unbounded => self.len() as u64,
-        };
+        self.try_slice(range).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        assert!(start <= end);
-        assert!(end <= self.len() as u64);
+    pub fn try_slice(
+        &self,
+        range: impl RangeBounds<usize>,
+    ) -> Result<RenderBufferSlice<'_>, RenderBufferBoundsError> {
+        let (start, end) = bounds(range, self.len())?;
 
-        RenderBufferSlice {
+        Ok(RenderBufferSlice {
             buf: &self.0,
-            start,
-            len: end - start,
-        }
+            start: start as u64,
+            len: (end - start) as u64,
+        })
     }
 
     pub fn index(&self, index: usize) -> RenderBufferRef<'_> {
-        assert!(index < self.len());
+        self.get(index).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        RenderBufferRef {
+    pub fn get(&self, index: usize) -> Result<RenderBufferRef<'_>, RenderBufferBoundsError> {
+        bounds(index..=index, self.len())?;
+
+        Ok(RenderBufferRef {
             buf: &self.0,
             index: index as u64,
-        }
+        })
     }
 
     pub fn write(&self, quads: &[Quad], ctx: RenderContext) {
@@ -84,43 +98,39 @@ impl<'a> RenderBufferSlice<'a> {
     }
 
     pub fn slice(&self, range: impl RangeBounds<usize>) -> RenderBufferSlice<'_> {
-        let start = match range.start_bound() {
-            Bound::Included(start) => *start as u64,
-            Bound::Excluded(start) => *start as u64 + 1,
-            Bound::Unbounded => 0,
-        };
-
-        let end = match range.start_bound() {
-            Bound::Included(end) => *end as u64 + 1,
-            Bound::Excluded(end) => *end as u64,
-            Bound::Unbounded => self.len() as u64,
-        };
+        self.try_slice(range).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        assert!(start <= end);
-        assert!(end <= self.len() as u64);
+    pub fn try_slice(
+        &self,
+        range: impl RangeBounds<usize>,
+    ) -> Result<RenderBufferSlice<'_>, RenderBufferBoundsError> {
+        let (start, end) = bounds(range, self.len())?;
 
-        RenderBufferSlice {
-            buf: &self.buf,
-            start: self.start + start,
-            len: end - start,
-        }
+        Ok(RenderBufferSlice {
+            buf: self.buf,
+            start: self.start + start as u64,
+            len: (end - start) as u64,
+        })
     }
 
     pub fn index(&self, index: usize) -> RenderBufferRef<'_> {
-        assert!(index < self.len());
+        self.get(index).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn get(&self, index: usize) -> Result<RenderBufferRef<'_>, RenderBufferBoundsError> {
+        bounds(index..=index, self.len())?;
 
-        RenderBufferRef {
-            buf: &self.buf,
+        Ok(RenderBufferRef {
+            buf: self.buf,
             index: self.start + index as u64,
-        }
+        })
     }
 
     pub fn write(&self, quads: &[Quad], ctx: RenderContext<'_>) {
         assert!(quads.len() <= self.len());
 
-        let quads_bytes = unsafe {
-            std::slice::from_raw_parts(quads.as_ptr().cast::<u8>(), quads.len() * size_of::<Quad>())
-        };
+        let quads_bytes = cast_slice::<Quad, u8>(quads);
 
         ctx.queue
             .write_buffer(self.buf, self.start * size_of::<Quad>() as u64, quads_bytes);
@@ -135,3 +145,227 @@ impl<'a> RenderBufferRef<'a> {
         ctx.queue.write_buffer(self.buf, offset, data);
     }
 }
+
+/// A [`RenderBuffer`] with a copy of its quads kept on the CPU, so edits only re-upload the
+/// range that changed since the last flush.
+///
+/// Suited to mostly static scenes, where rewriting the whole buffer every frame would upload
+/// the same data again.
+#[derive(Debug, Clone)]
+pub struct TrackedRenderBuffer {
+    buf: RenderBuffer,
+    quads: Vec<Quad>,
+    dirty: Option<Range<usize>>,
+}
+
+impl Display for RenderBufferBoundsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "quads {}..{} are out of bounds of a render buffer of {} quads",
+            self.start, self.end, self.len
+        )
+    }
+}
+
+impl Error for RenderBufferBoundsError {}
+
+impl TrackedRenderBuffer {
+    pub fn new(quads: Vec<Quad>, ctx: RenderContext) -> Self {
+        Self {
+            buf: RenderBuffer::new(&quads, ctx),
+            quads,
+            dirty: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.quads.len()
+    }
+
+    pub fn get(&self, index: usize) -> Quad {
+        self.quads[index]
+    }
+
+    /// Marks the quad dirty, skipping it if it is unchanged.
+    pub fn set(&mut self, index: usize, quad: Quad) {
+        if self.quads[index] == quad {
+            return;
+        }
+
+        self.quads[index] = quad;
+
+        self.dirty = Some(match self.dirty.take() {
+            Some(dirty) => dirty.start.min(index)..dirty.end.max(index + 1),
+            None => index..index + 1,
+        });
+    }
+
+    /// Uploads the dirty range, a single quad through [`RenderBufferRef::write`].
+    pub fn flush(&mut self, ctx: RenderContext) {
+        let Some(dirty) = self.dirty.take() else {
+            return;
+        };
+
+        if dirty.len() == 1 {
+            self.buf
+                .index(dirty.start)
+                .write(&self.quads[dirty.start], ctx);
+        } else {
+            self.buf.slice(dirty.clone()).write(&self.quads[dirty], ctx);
+        }
+    }
+
+    /// Flushes and returns the whole buffer for drawing.
+    pub fn slice(&mut self, ctx: RenderContext) -> RenderBufferSlice<'_> {
+        self.flush(ctx);
+
+        self.buf.slice(..)
+    }
+}
+
+fn bounds(
+    range: impl RangeBounds<usize>,
+    len: usize,
+) -> Result<(usize, usize), RenderBufferBoundsError> {
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(end) => end.saturating_add(1),
+        Bound::Excluded(end) => *end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end || end > len {
+        return Err(RenderBufferBoundsError { start, end, len });
+    }
+
+    Ok((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use glam::vec2;
+    use wgpu::{PollType, TextureFormat};
+
+    use super::*;
+    use crate::renderer::renderer::tests::device;
+
+    #[test]
+    fn bounds_resolves_ranges() {
+        assert_eq!(bounds(.., 4), Ok((0, 4)));
+        assert_eq!(bounds(1..=2, 4), Ok((1, 3)));
+        assert_eq!(bounds(4..4, 4), Ok((4, 4)));
+        assert_eq!(
+            bounds(2..5, 4),
+            Err(RenderBufferBoundsError {
+                start: 2,
+                end: 5,
+                len: 4
+            })
+        );
+        assert_eq!(
+            bounds((Bound::Included(3), Bound::Excluded(2)), 4),
+            Err(RenderBufferBoundsError {
+                start: 3,
+                end: 2,
+                len: 4
+            })
+        );
+        assert!(bounds(..=usize::MAX, 4).is_err());
+    }
+
+    #[test]
+    fn try_slice_and_get_report_the_buffer_length() {
+        let Some((device, queue)) = device() else {
+            return;
+        };
+        let ctx = RenderContext {
+            device: &device,
+            queue: &queue,
+            surface_format: TextureFormat::Rgba8UnormSrgb,
+        };
+
+        let buf = RenderBuffer::new_uninit(8, ctx);
+        let slice = buf.try_slice(2..6).unwrap();
+        assert_eq!(slice.len(), 4);
+
+        assert_eq!(
+            slice.try_slice(1..5).map(|slice| slice.len()),
+            Err(RenderBufferBoundsError {
+                start: 1,
+                end: 5,
+                len: 4
+            })
+        );
+        assert_eq!(
+            buf.get(8).map(|_| ()),
+            Err(RenderBufferBoundsError {
+                start: 8,
+                end: 9,
+                len: 8
+            })
+        );
+        assert!(slice.get(3).is_ok());
+        assert!(slice.get(4).is_err());
+    }
+
+    /// Compares re-uploading a whole static scene every frame with flushing one edited quad.
+    ///
+    /// The crate is a binary, so this lives here instead of under `benches/`. Run it with
+    /// `cargo test --release tracked_upload_benchmark -- --ignored --nocapture`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn tracked_upload_benchmark() {
+        const QUADS: usize = 10_000;
+        const FRAMES: usize = 500;
+
+        let Some((device, queue)) = device() else {
+            return;
+        };
+        let ctx = RenderContext {
+            device: &device,
+            queue: &queue,
+            surface_format: TextureFormat::Rgba8UnormSrgb,
+        };
+
+        let quads = (0..QUADS)
+            .map(|i| Quad::at(vec2(i as f32, 0.0)))
+            .collect::<Vec<_>>();
+
+        let full = RenderBuffer::new(&quads, ctx);
+        let start = Instant::now();
+        for frame in 0..FRAMES {
+            let mut quads = quads.clone();
+            quads[frame % QUADS].center.y += 1.0;
+
+            full.write(&quads, ctx);
+            queue.submit([]);
+        }
+        device.poll(PollType::wait_indefinitely()).unwrap();
+        let full_time = start.elapsed();
+
+        let mut tracked = TrackedRenderBuffer::new(quads.clone(), ctx);
+        let start = Instant::now();
+        for frame in 0..FRAMES {
+            let mut quad = tracked.get(frame % QUADS);
+            quad.center.y += 1.0;
+
+            tracked.set(frame % QUADS, quad);
+            tracked.flush(ctx);
+            queue.submit([]);
+        }
+        device.poll(PollType::wait_indefinitely()).unwrap();
+        let tracked_time = start.elapsed();
+
+        println!(
+            "{FRAMES} frames of {QUADS} quads: full rewrite {full_time:?}, tracked {tracked_time:?}"
+        );
+    }
+}
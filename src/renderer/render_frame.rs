@@ -1,62 +1,302 @@
-use bytemuck::bytes_of;
-use glam::vec2;
+use bytemuck::{bytes_of, cast_slice};
+use glam::{Vec2, Vec4, vec2};
 use wgpu::{
-    Color, CommandEncoderDescriptor, IndexFormat, LoadOp, Operations, RenderPassColorAttachment,
-    RenderPassDescriptor, StoreOp, TextureView,
+    BufferSize, BufferUsages, Color, CommandEncoder, CommandEncoderDescriptor, IndexFormat, LoadOp,
+    Operations, RenderPassColorAttachment, RenderPassDescriptor, StoreOp, TextureView,
+    util::{BufferInitDescriptor, DeviceExt},
 };
 
-use crate::renderer::{ASPECT, RenderContext, Renderer, UpscaleUniform};
+use crate::{
+    renderer::{
+        Camera, PIXELS_PER_UNIT, Quad, Rect, RenderContext, RenderLayer, Renderer, Sprite,
+        UpscaleMode, UpscaleShader, UpscaleUniform, clamp_ortho_size,
+    },
+    trace_span,
+};
 
 pub struct RenderFrame<'a> {
     pub(in crate::renderer) renderer: &'a mut Renderer,
     pub(in crate::renderer) output: &'a TextureView,
-    pub(in crate::renderer) ctx: &'a RenderContext<'a>,
+    pub(in crate::renderer) ctx: RenderContext<'a>,
+    pub(in crate::renderer) encoder: Option<CommandEncoder>,
     pub(in crate::renderer) has_rendered: bool,
+    pub(in crate::renderer) stats: RenderStats,
+    pub(in crate::renderer) src_rect: Rect,
+    pub(in crate::renderer) cursor: Option<(Quad, Camera)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RenderStats {
+    pub draw_calls: u32,
+    pub quads: u32,
+    pub debug_lines: u32,
+    pub culled_quads: u32,
 }
 
 impl Renderer {
+    /// Renders a frame and upscales it into `output`, letterboxed to the output's own size.
+    ///
+    /// The output does not have to be the surface texture, any view in the surface format
+    /// works, such as a [`RenderTarget`](crate::renderer::RenderTarget).
     pub fn render_frame(
         &mut self,
         f: impl FnOnce(&mut RenderFrame),
         output: &TextureView,
         ctx: RenderContext,
     ) {
-        f(&mut RenderFrame {
+        trace_span!("render_frame");
+
+        let mut frame = self.begin_frame(output, ctx);
+        f(&mut frame);
+        frame.finish();
+    }
+
+    /// Starts a frame without a closure, for draw code spread over several methods. The frame
+    /// is upscaled and submitted by [`RenderFrame::finish`], or when it is dropped.
+    pub fn begin_frame<'a>(
+        &'a mut self,
+        output: &'a TextureView,
+        ctx: RenderContext<'a>,
+    ) -> RenderFrame<'a> {
+        let encoder = ctx
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor::default());
+
+        RenderFrame {
             renderer: self,
             output,
-            ctx: &ctx,
+            ctx,
+            encoder: Some(encoder),
             has_rendered: false,
-        })
+            stats: RenderStats::default(),
+            src_rect: Rect {
+                min: Vec2::ZERO,
+                max: Vec2::ONE,
+            },
+            cursor: None,
+        }
     }
 }
 
-impl<'a> Drop for RenderFrame<'a> {
-    fn drop(&mut self) {
-        let output_aspect =
-            self.output.texture().width() as f32 / self.output.texture().height() as f32;
+impl Renderer {
+    /// Renders the same scene once per view, each with its own camera, into a rect of the
+    /// screen given in normalized coordinates with the origin at the top left.
+    ///
+    /// A two-player horizontal split:
+    ///
+    /// ```ignore
+    /// renderer.render_viewports(
+    ///     &[
+    ///         (Camera::new(player_1), Rect { min: vec2(0.0, 0.0), max: vec2(0.5, 1.0) }),
+    ///         (Camera::new(player_2), Rect { min: vec2(0.5, 0.0), max: vec2(1.0, 1.0) }),
+    ///     ],
+    ///     |r, _| draw_world(r),
+    ///     output,
+    ///     ctx.into(),
+    /// );
+    /// ```
+    pub fn render_viewports(
+        &mut self,
+        views: &[(Camera, Rect)],
+        mut f: impl FnMut(&mut RenderLayer, usize),
+        output: &TextureView,
+        ctx: RenderContext,
+    ) {
+        self.render_frame(
+            |r| {
+                for (index, (camera, viewport)) in views.iter().enumerate() {
+                    r.render_viewport_layer(|r| f(r, index), *camera, *viewport);
+                }
+            },
+            output,
+            ctx,
+        );
+    }
+}
+
+impl Renderer {
+    /// Maps a position in output pixels, such as `GameEvent::MouseMove`, to the world position
+    /// under it for a full-screen layer drawn with `camera`. Returns `None` over the letterbox.
+    pub fn screen_to_world(
+        &self,
+        screen_pos: Vec2,
+        output_size: Vec2,
+        camera: Camera,
+    ) -> Option<Vec2> {
+        let (dst_extents, dst_offset) = self.upscale_dst(output_size);
+
+        let output_ndc = (screen_pos / output_size * 2.0 - 1.0) * vec2(1.0, -1.0);
+        let ndc = (output_ndc - dst_offset) / dst_extents;
+        if ndc.abs().cmpgt(Vec2::ONE).any() {
+            return None;
+        }
 
-        let dst_extents = if output_aspect < ASPECT {
-            vec2(1.0, 1.0 * output_aspect / ASPECT)
+        let rotation = Vec2::from_angle(-camera.rotation);
+        let center = rotation.rotate(camera.center);
+        let center = if camera.pixel_snap {
+            (center * PIXELS_PER_UNIT).floor() / PIXELS_PER_UNIT
         } else {
-            vec2(1.0 * ASPECT / output_aspect, 1.0)
+            center
         };
 
-        let upscale_uniform = UpscaleUniform { dst_extents };
+        let cam_pos = ndc * vec2(self.aspect, 1.0) * clamp_ortho_size(camera.ortho_size);
 
-        self.ctx.queue.write_buffer(
-            &self.renderer.upscale_uniform_buf,
-            0,
-            bytes_of::<UpscaleUniform>(&upscale_uniform),
+        Some(Vec2::from_angle(camera.rotation).rotate(cam_pos + center))
+    }
+
+    /// The extents and offset of the upscaled image in the output's normalized coordinates.
+    pub(in crate::renderer) fn upscale_dst(&self, output_size: Vec2) -> (Vec2, Vec2) {
+        let render_texture = self.render_texture.texture();
+        let render_size = vec2(
+            render_texture.width() as f32,
+            render_texture.height() as f32,
         );
 
-        let mut encoder = self
-            .ctx
-            .device
-            .create_command_encoder(&CommandEncoderDescriptor::default());
+        upscale_dst(self.upscale_mode, self.aspect, render_size, output_size)
+    }
+}
+
+impl<'a> RenderFrame<'a> {
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    /// Upscales the frame into its output and submits it. Dropping the frame does the same.
+    pub fn finish(mut self) {
+        self.submit();
+    }
+
+    pub fn set_src_rect(&mut self, rect: Rect) {
+        let min = rect.min.clamp(Vec2::ZERO, Vec2::ONE);
+        let max = rect.max.clamp(min, Vec2::ONE);
+
+        self.src_rect = Rect { min, max };
+    }
+
+    /// Draws a cursor sprite from the built-in atlas at `world_pos`, above every layer of the
+    /// frame no matter when it is called. For games that hide the OS cursor.
+    ///
+    /// ```ignore
+    /// let cursor_pos = renderer.screen_to_world(self.mouse_pos, output_size, camera);
+    ///
+    /// renderer.render_frame(
+    ///     |r| {
+    ///         if let Some(cursor_pos) = cursor_pos {
+    ///             r.draw_cursor(CROSSHAIR, cursor_pos, camera);
+    ///         }
+    ///
+    ///         r.render_layer(|r| draw_world(r), camera);
+    ///     },
+    ///     output,
+    ///     ctx.into(),
+    /// );
+    /// ```
+    pub fn draw_cursor(&mut self, sprite: Sprite, world_pos: Vec2, camera: Camera) {
+        self.cursor = Some((Quad::at(world_pos).sprite(sprite), camera));
+    }
+}
+
+impl<'a> RenderFrame<'a> {
+    /// Does the work of [`RenderFrame::finish`] once, whether the frame is finished or dropped.
+    fn submit(&mut self) {
+        if let Some((cursor, camera)) = self.cursor.take() {
+            self.render_layer(|r| r.render_quad(cursor), camera);
+        }
+
+        let Some(mut encoder) = self.encoder.take() else {
+            return;
+        };
+
+        if !self.has_rendered {
+            encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("renderer clear render pass"),
+                color_attachments: &[Some(
+                    self.renderer
+                        .render_texture_attachment(LoadOp::Clear(Color::BLACK)),
+                )],
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                depth_stencil_attachment: None,
+                multiview_mask: None,
+            });
+        }
+
+        if !self.renderer.debug_vertex_vec.is_empty() {
+            let debug_vertex_buf = self.ctx.device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("renderer debug vertex buffer"),
+                contents: cast_slice(&self.renderer.debug_vertex_vec),
+                usage: BufferUsages::VERTEX,
+            });
+
+            let mut debug_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("renderer debug render pass"),
+                color_attachments: &[Some(self.renderer.render_texture_attachment(LoadOp::Load))],
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                depth_stencil_attachment: None,
+                multiview_mask: None,
+            });
+
+            debug_pass.set_vertex_buffer(0, debug_vertex_buf.slice(..));
+            debug_pass.set_pipeline(&self.renderer.debug_pipeline);
+
+            debug_pass.draw(0..self.renderer.debug_vertex_vec.len() as u32, 0..1);
+
+            drop(debug_pass);
+
+            self.renderer.debug_vertex_vec.clear();
+        }
+
+        trace_span!("upscale");
+
+        let output_size = vec2(
+            self.output.texture().width() as f32,
+            self.output.texture().height() as f32,
+        );
+
+        let (dst_extents, dst_offset) = self.renderer.upscale_dst(output_size);
+
+        let fade = self.renderer.fade;
+        let letterbox_color = self.renderer.letterbox_color;
+        let letterbox_color =
+            letterbox_color.lerp(fade.truncate().extend(letterbox_color.w), fade.w);
+
+        let (scanline_strength, curvature) = match self.renderer.upscale_shader {
+            UpscaleShader::Plain => (0.0, 0.0),
+            UpscaleShader::Crt {
+                scanline_strength,
+                curvature,
+            } => (scanline_strength, curvature),
+        };
+
+        let upscale_uniform = UpscaleUniform {
+            dst_extents,
+            dst_offset,
+            src_extents: self.src_rect.extents(),
+            src_offset: self.src_rect.center() - 0.5,
+            src_height: self.renderer.render_texture.texture().height() as f32,
+            scanline_strength,
+            curvature,
+            brightness: self.renderer.brightness,
+            contrast: self.renderer.contrast,
+            gamma: self.renderer.gamma,
+            _padding: [0.0; 2],
+            fade: (fade.truncate() * fade.w).extend(fade.w),
+        };
+
+        self.renderer
+            .staging_belt
+            .write_buffer(
+                &mut encoder,
+                &self.renderer.upscale_uniform_buf,
+                0,
+                BufferSize::new(size_of::<UpscaleUniform>() as u64).unwrap(),
+            )
+            .copy_from_slice(bytes_of::<UpscaleUniform>(&upscale_uniform));
 
         {
             let mut upscale_pass = encoder.begin_render_pass(&RenderPassDescriptor {
-                label: Some("lib_renderer upscale render pass"),
+                label: Some("renderer upscale render pass"),
                 timestamp_writes: None,
                 occlusion_query_set: None,
                 depth_stencil_attachment: None,
@@ -66,10 +306,10 @@ impl<'a> Drop for RenderFrame<'a> {
                     depth_slice: None,
                     ops: Operations {
                         load: LoadOp::Clear(Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 0.0,
+                            r: letterbox_color.x as f64,
+                            g: letterbox_color.y as f64,
+                            b: letterbox_color.z as f64,
+                            a: letterbox_color.w as f64,
                         }),
                         store: StoreOp::Store,
                     },
@@ -85,6 +325,140 @@ impl<'a> Drop for RenderFrame<'a> {
             upscale_pass.draw_indexed(0..6, 0, 0..1);
         }
 
+        self.renderer.staging_belt.finish();
         self.ctx.queue.submit([encoder.finish()]);
+        self.renderer.staging_belt.recall();
+    }
+}
+
+impl<'a> Drop for RenderFrame<'a> {
+    fn drop(&mut self) {
+        self.submit();
+    }
+}
+
+/// Where a render texture of `render_size` pixels and `aspect` lands when upscaled into an
+/// output of `output_size` pixels, as extents and offset in the output's normalized coordinates.
+fn upscale_dst(
+    upscale_mode: UpscaleMode,
+    aspect: f32,
+    render_size: Vec2,
+    output_size: Vec2,
+) -> (Vec2, Vec2) {
+    match upscale_mode {
+        UpscaleMode::Fit => {
+            let output_aspect = output_size.x / output_size.y;
+
+            let dst_extents = if output_aspect < aspect {
+                vec2(1.0, 1.0 * output_aspect / aspect)
+            } else {
+                vec2(1.0 * aspect / output_aspect, 1.0)
+            };
+
+            (dst_extents, Vec2::ZERO)
+        }
+
+        UpscaleMode::IntegerScale => {
+            let scale = (output_size / render_size).min_element().floor().max(1.0);
+            let dst_size = render_size * scale;
+            let dst_min = ((output_size - dst_size) / 2.0).floor();
+            let dst_center = dst_min + dst_size / 2.0;
+
+            (
+                dst_size / output_size,
+                (dst_center / output_size * 2.0 - 1.0) * vec2(1.0, -1.0),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::TextureFormat;
+
+    use super::*;
+    use crate::renderer::{AspectRatio, RendererConfig, renderer::tests::device};
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(actual.abs_diff_eq(expected, 1e-5), "{actual} != {expected}");
+    }
+
+    #[test]
+    fn square_aspect_letterboxes_both_ways() {
+        let render_size = Vec2::splat(320.0);
+        let fit = |output_size| upscale_dst(UpscaleMode::Fit, 1.0, render_size, output_size);
+
+        let (extents, offset) = fit(vec2(1600.0, 900.0));
+        assert_near(extents, vec2(900.0 / 1600.0, 1.0));
+        assert_eq!(offset, Vec2::ZERO);
+
+        let (extents, offset) = fit(vec2(900.0, 1600.0));
+        assert_near(extents, vec2(1.0, 900.0 / 1600.0));
+        assert_eq!(offset, Vec2::ZERO);
+
+        // The square render texture is 320 pixels, which fits twice into 700.
+        let (extents, offset) = upscale_dst(
+            UpscaleMode::IntegerScale,
+            1.0,
+            render_size,
+            vec2(1000.0, 700.0),
+        );
+        assert_near(extents, vec2(640.0 / 1000.0, 640.0 / 700.0));
+        assert_near(offset, Vec2::ZERO);
+    }
+
+    #[test]
+    fn integer_scale_centers_on_whole_pixels() {
+        // Twice 320x180 leaves 41 spare rows in 401. Only 20 go above the image, so its center
+        // sits half a pixel above the output's.
+        let (extents, offset) = upscale_dst(
+            UpscaleMode::IntegerScale,
+            16.0 / 9.0,
+            vec2(320.0, 180.0),
+            vec2(1000.0, 401.0),
+        );
+        assert_near(extents, vec2(640.0 / 1000.0, 360.0 / 401.0));
+        assert_near(offset, vec2(0.0, 1.0 / 401.0));
+    }
+
+    #[test]
+    fn visible_rect_matches_screen_to_world_corners() {
+        let Some((device, queue)) = device() else {
+            return;
+        };
+        let renderer = Renderer::new_headless(
+            &RendererConfig::default(),
+            &device,
+            &queue,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+
+        let camera = Camera {
+            pixel_snap: false,
+            ..Camera::new(vec2(2.0, -3.0))
+        };
+        let rect = camera.visible_rect(renderer.aspect());
+
+        // A wider output, so the corners of the image are inside the letterbox. They are
+        // nudged inward, since rounding can put the exact corner just outside.
+        let output_size = vec2(1600.0, 720.0);
+        let image_min = vec2((1600.0 - 1280.0) / 2.0, 0.0) + 0.001;
+        let image_max = image_min + vec2(1280.0, 720.0) - 0.002;
+
+        let top_left = renderer.screen_to_world(image_min, output_size, camera);
+        let bottom_right = renderer.screen_to_world(image_max, output_size, camera);
+
+        for (world, expected) in [
+            (top_left, vec2(rect.min.x, rect.max.y)),
+            (bottom_right, vec2(rect.max.x, rect.min.y)),
+        ] {
+            let world = world.unwrap();
+            assert!(world.abs_diff_eq(expected, 1e-3), "{world} != {expected}");
+        }
+
+        assert_eq!(
+            renderer.screen_to_world(vec2(1.0, 360.0), output_size, camera),
+            None
+        );
     }
 }
@@ -1,93 +1,341 @@
-use bytemuck::bytes_of;
-use glam::Vec2;
+use std::array;
+
+use bytemuck::{bytes_of, cast_slice};
+use glam::{U8Vec4, Vec2, Vec4, vec2};
 use wgpu::{
-    Color, CommandEncoderDescriptor, IndexFormat, LoadOp, Operations, RenderPassColorAttachment,
+    BufferSize, Color, CommandEncoder, IndexFormat, LoadOp, Operations, RenderPassColorAttachment,
     RenderPassDescriptor, StoreOp,
 };
 
-use crate::renderer::{
-    DYN_QUAD_CAP, PIXELS_PER_UNIT, Quad, RenderBufferSlice, RenderContext, RenderFrame,
-    RenderUniform, Renderer,
+use crate::{
+    renderer::{
+        AtlasId, BlendMode, Camera, DYN_QUAD_CAP, PIXELS_PER_UNIT, Quad, Rect, RenderBufferSlice,
+        RenderContext, RenderFrame, RenderStats, RenderUniform, Renderer, Sprite, clamp_ortho_size,
+        color_to_tint, rotated_view_rect,
+    },
+    trace_warn,
 };
 
 pub struct RenderLayer<'a> {
     pub(in crate::renderer) renderer: &'a mut Renderer,
     pub(in crate::renderer) ctx: &'a RenderContext<'a>,
+    pub(in crate::renderer) encoder: &'a mut CommandEncoder,
     pub(in crate::renderer) has_rendered: &'a mut bool,
+    pub(in crate::renderer) stats: &'a mut RenderStats,
+    pub(in crate::renderer) scissor: Option<Rect>,
+    pub(in crate::renderer) uniform: RenderUniform,
+    pub(in crate::renderer) viewport: Rect,
+    pub(in crate::renderer) blend_mode: BlendMode,
 }
 
 impl<'a> RenderFrame<'a> {
-    pub fn render_layer(&mut self, f: impl FnOnce(&mut RenderLayer), camera_center: Vec2) {
+    pub fn render_layer(&mut self, f: impl FnOnce(&mut RenderLayer), camera: Camera) {
+        self.render_viewport_layer(
+            f,
+            camera,
+            Rect {
+                min: Vec2::ZERO,
+                max: Vec2::ONE,
+            },
+        );
+    }
+
+    pub fn render_viewport_layer(
+        &mut self,
+        f: impl FnOnce(&mut RenderLayer),
+        camera: Camera,
+        viewport: Rect,
+    ) {
+        let viewport_min = viewport.min.clamp(Vec2::ZERO, Vec2::ONE);
+        let viewport = Rect {
+            min: viewport_min,
+            max: viewport.max.clamp(viewport_min, Vec2::ONE),
+        };
+
+        if viewport.size().min_element() <= 0.0 {
+            return;
+        }
+
+        let cam_ortho_size = clamp_ortho_size(camera.ortho_size);
+        if cam_ortho_size != camera.ortho_size {
+            trace_warn!(
+                "camera ortho size {} is out of range, clamped to {cam_ortho_size}",
+                camera.ortho_size,
+            );
+        }
+
+        let cam_rotation = Vec2::from_angle(-camera.rotation);
+        let cam_center = cam_rotation.rotate(camera.center);
+        let cam_center = if camera.pixel_snap {
+            (cam_center * PIXELS_PER_UNIT).floor() / PIXELS_PER_UNIT
+        } else {
+            cam_center
+        };
+
         let render_uniform = RenderUniform {
-            cam_center: (camera_center * PIXELS_PER_UNIT).floor() / PIXELS_PER_UNIT,
+            cam_center,
+            cam_rotation,
+            cam_ortho_size,
+            cam_aspect: self.renderer.aspect * viewport.size().x / viewport.size().y,
         };
 
-        self.ctx.queue.write_buffer(
-            &self.renderer.render_uniform_buf,
-            0,
-            bytes_of::<RenderUniform>(&render_uniform),
-        );
+        self.renderer
+            .staging_belt
+            .write_buffer(
+                self.encoder.as_mut().unwrap(),
+                &self.renderer.render_uniform_buf,
+                0,
+                BufferSize::new(size_of::<RenderUniform>() as u64).unwrap(),
+            )
+            .copy_from_slice(bytes_of::<RenderUniform>(&render_uniform));
 
         f(&mut RenderLayer {
             renderer: self.renderer,
-            ctx: self.ctx,
+            ctx: &self.ctx,
+            encoder: self.encoder.as_mut().unwrap(),
             has_rendered: &mut self.has_rendered,
+            stats: &mut self.stats,
+            scissor: None,
+            uniform: render_uniform,
+            viewport,
+            blend_mode: BlendMode::Alpha,
         })
     }
+
+    pub fn render_parallax_layer(
+        &mut self,
+        f: impl FnOnce(&mut RenderLayer),
+        camera: Camera,
+        parallax: f32,
+    ) {
+        self.render_layer(
+            f,
+            Camera {
+                center: camera.center * parallax,
+                ..camera
+            },
+        );
+    }
 }
 
 impl<'a> RenderLayer<'a> {
+    /// Blends everything drawn after this call with `blend_mode` until it is changed again.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        if blend_mode != self.blend_mode {
+            self.flush_dyn_quads();
+            self.blend_mode = blend_mode;
+        }
+    }
+
     pub fn render_quad(&mut self, quad: Quad) {
+        if let Some(last) = self.renderer.dyn_quad_vec.last()
+            && last.atlas != quad.atlas
+        {
+            self.flush_dyn_quads();
+        }
+
         self.renderer.dyn_quad_vec.push(quad);
 
         if self.renderer.dyn_quad_vec.len() == DYN_QUAD_CAP {
-            self.renderer
-                .dyn_quad_buf
-                .write(&self.renderer.dyn_quad_vec, *self.ctx);
+            self.flush_dyn_quads();
+        }
+    }
+
+    /// Same as calling [`RenderLayer::render_quad`] for each quad.
+    pub fn render_quads(&mut self, quads: impl IntoIterator<Item = Quad>) {
+        for quad in quads {
+            self.render_quad(quad);
+        }
+    }
+
+    /// Same as calling [`RenderLayer::render_quad`] for each quad, but copies runs of quads
+    /// with the same atlas at once.
+    pub fn render_quad_slice(&mut self, mut quads: &[Quad]) {
+        while let Some(first) = quads.first() {
+            if let Some(last) = self.renderer.dyn_quad_vec.last()
+                && last.atlas != first.atlas
+            {
+                self.flush_dyn_quads();
+            }
+
+            let same_atlas = quads
+                .iter()
+                .position(|quad| quad.atlas != first.atlas)
+                .unwrap_or(quads.len());
 
-            self.render_buffer_shared(self.renderer.dyn_quad_buf.slice(..));
-            *self.has_rendered = true;
+            let space = DYN_QUAD_CAP - self.renderer.dyn_quad_vec.len();
+            let (run, rest) = quads.split_at(same_atlas.min(space));
 
-            self.renderer.dyn_quad_vec.clear();
+            self.renderer.dyn_quad_vec.extend_from_slice(run);
+            quads = rest;
+
+            if self.renderer.dyn_quad_vec.len() == DYN_QUAD_CAP {
+                self.flush_dyn_quads();
+            }
         }
     }
 
-    pub fn render_buffer(&mut self, quads: RenderBufferSlice<'_>) {
-        self.render_buffer_shared(quads);
-        *self.has_rendered = true;
+    /// Draws a solid rectangle by tinting the renderer's built-in 1x1 white atlas.
+    pub fn draw_rect(&mut self, center: Vec2, extents: Vec2, color: Vec4) {
+        self.render_quad(Quad {
+            center,
+            sprite: Sprite {
+                center: Vec2::splat(0.5),
+                extents: Vec2::splat(0.5),
+            },
+            layer: 0.0,
+            atlas: self.renderer.white_atlas,
+            scale: extents * 2.0 * PIXELS_PER_UNIT,
+            tint: color_to_tint(color),
+        });
+    }
+
+    /// Stretches a sprite over `rect` while keeping its corners at a fixed size, for panels
+    /// and buttons.
+    ///
+    /// `border` is the corner size in world units. The sprite's corners are the same size in
+    /// its own pixels, so the border has to be less than half the sprite's size.
+    pub fn draw_nine_slice(&mut self, rect: Rect, sprite: Sprite, atlas: AtlasId, border: Vec2) {
+        let atlas_size = self.renderer.atlas_sizes[atlas.0 as usize];
+
+        for quad in nine_slice_quads(rect, sprite, atlas, border, atlas_size) {
+            self.render_quad(quad);
+        }
+    }
+
+    pub fn with_scissor(&mut self, rect: Rect, f: impl FnOnce(&mut RenderLayer)) {
+        self.flush_dyn_quads();
+
+        let render_size = vec2(
+            self.renderer.render_texture.texture().width() as f32,
+            self.renderer.render_texture.texture().height() as f32,
+        );
+
+        let bounds = self.scissor.unwrap_or(Rect {
+            min: (self.viewport.min * render_size).floor(),
+            max: (self.viewport.max * render_size).ceil(),
+        });
+
+        let min = rect.min.floor().clamp(bounds.min, bounds.max);
+        let max = rect.max.ceil().clamp(min, bounds.max);
+
+        let outer_scissor = self.scissor.replace(Rect { min, max });
+
+        f(self);
+
+        self.flush_dyn_quads();
+        self.scissor = outer_scissor;
     }
 
-    fn render_buffer_shared(&self, quads: RenderBufferSlice<'_>) {
-        let mut encoder = self
-            .ctx
-            .device
-            .create_command_encoder(&CommandEncoderDescriptor::default());
+    pub fn render_quad_culled(&mut self, quad: Quad) {
+        let quad_extents = quad.sprite.extents.abs()
+            * self.renderer.atlas_sizes[quad.atlas.0 as usize]
+            / PIXELS_PER_UNIT
+            * quad.scale.abs();
+
+        let visible_rect = self.visible_rect();
 
-        let load_op;
-        if *self.has_rendered {
-            load_op = LoadOp::Load;
+        if (quad.center - visible_rect.center())
+            .abs()
+            .cmpgt(visible_rect.extents() + quad_extents)
+            .any()
+        {
+            self.stats.culled_quads += 1;
+            return;
+        }
+
+        self.render_quad(quad);
+    }
+
+    pub fn visible_rect(&self) -> Rect {
+        rotated_view_rect(
+            self.uniform.cam_center,
+            self.uniform.cam_rotation,
+            vec2(self.uniform.cam_aspect, 1.0) * self.uniform.cam_ortho_size,
+        )
+    }
+
+    /// Draws quads from a buffer that persists across frames, without uploading them again.
+    pub fn render_buffer(&mut self, quads: RenderBufferSlice<'_>, atlas: AtlasId) {
+        self.flush_dyn_quads();
+
+        Self::render_buffer_shared(
+            self.encoder,
+            self.renderer,
+            *self.has_rendered,
+            self.scissor,
+            self.viewport,
+            self.blend_mode,
+            quads,
+            atlas,
+        );
+        self.finish_draw(quads.len());
+    }
+
+    fn flush_dyn_quads(&mut self) {
+        let Some(first) = self.renderer.dyn_quad_vec.first() else {
+            return;
+        };
+
+        let atlas = first.atlas;
+
+        let quads = self
+            .renderer
+            .dyn_quad_buf
+            .slice(..self.renderer.dyn_quad_vec.len());
+
+        self.renderer
+            .staging_belt
+            .write_buffer(
+                self.encoder,
+                quads.buf,
+                quads.start * size_of::<Quad>() as u64,
+                BufferSize::new(quads.len * size_of::<Quad>() as u64).unwrap(),
+            )
+            .copy_from_slice(cast_slice(&self.renderer.dyn_quad_vec));
+
+        Self::render_buffer_shared(
+            self.encoder,
+            self.renderer,
+            *self.has_rendered,
+            self.scissor,
+            self.viewport,
+            self.blend_mode,
+            quads,
+            atlas,
+        );
+        self.finish_draw(self.renderer.dyn_quad_vec.len());
+
+        self.renderer.dyn_quad_vec.clear();
+    }
+
+    #[expect(clippy::too_many_arguments)]
+    fn render_buffer_shared(
+        encoder: &mut CommandEncoder,
+        renderer: &Renderer,
+        has_rendered: bool,
+        scissor: Option<Rect>,
+        viewport: Rect,
+        blend_mode: BlendMode,
+        quads: RenderBufferSlice<'_>,
+        atlas: AtlasId,
+    ) {
+        let load_op = if has_rendered {
+            LoadOp::Load
         } else {
-            load_op = LoadOp::Clear(Color::BLACK);
+            LoadOp::Clear(Color::BLACK)
         };
 
         let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
             label: Some("renderer render pass"),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: &self.renderer.render_texture,
-                ops: Operations {
-                    load: load_op,
-                    store: StoreOp::Store,
-                },
-                depth_slice: None,
-                resolve_target: None,
-            })],
+            color_attachments: &[Some(renderer.render_texture_attachment(load_op))],
             timestamp_writes: None,
             occlusion_query_set: None,
             depth_stencil_attachment: None,
             multiview_mask: None,
         });
 
-        pass.set_vertex_buffer(0, self.renderer.vertex_buf.slice(..));
+        pass.set_vertex_buffer(0, renderer.vertex_buf.slice(..));
         pass.set_vertex_buffer(
             1,
             quads.buf.slice(
@@ -95,33 +343,130 @@ impl<'a> RenderLayer<'a> {
                     ..(quads.start + quads.len) * size_of::<Quad>() as u64,
             ),
         );
-        pass.set_index_buffer(self.renderer.index_buf.slice(..), IndexFormat::Uint16);
-        pass.set_bind_group(0, &self.renderer.render_bind_group, &[]);
-        pass.set_pipeline(&self.renderer.render_pipeline);
+        pass.set_index_buffer(renderer.index_buf.slice(..), IndexFormat::Uint16);
+        pass.set_bind_group(0, &renderer.atlases[atlas.0 as usize], &[]);
+        pass.set_pipeline(&renderer.render_pipelines[blend_mode as usize]);
+
+        let render_size = vec2(
+            renderer.render_texture.texture().width() as f32,
+            renderer.render_texture.texture().height() as f32,
+        );
+        let viewport_min = viewport.min * render_size;
+        let viewport_size = viewport.size() * render_size;
+
+        pass.set_viewport(
+            viewport_min.x,
+            viewport_min.y,
+            viewport_size.x,
+            viewport_size.y,
+            0.0,
+            1.0,
+        );
+
+        if let Some(scissor) = scissor {
+            let size = scissor.size();
+
+            pass.set_scissor_rect(
+                scissor.min.x as u32,
+                scissor.min.y as u32,
+                size.x as u32,
+                size.y as u32,
+            );
+        }
 
         pass.draw_indexed(0..6, 0, 0..quads.len() as u32);
+    }
 
-        drop(pass);
+    fn finish_draw(&mut self, quad_count: usize) {
+        *self.has_rendered = true;
 
-        self.ctx.queue.submit([encoder.finish()]);
+        self.stats.draw_calls += 1;
+        self.stats.quads += quad_count as u32;
     }
 }
 
 impl<'a> Drop for RenderLayer<'a> {
     fn drop(&mut self) {
-        if self.renderer.dyn_quad_vec.len() > 0 {
-            self.renderer
-                .dyn_quad_buf
-                .write(&self.renderer.dyn_quad_vec, *self.ctx);
-
-            self.render_buffer_shared(
-                self.renderer
-                    .dyn_quad_buf
-                    .slice(..self.renderer.dyn_quad_vec.len()),
-            );
-            *self.has_rendered = true;
+        self.flush_dyn_quads();
+    }
+}
+
+/// The quads of a nine-slice, row by row from the bottom left. `atlas_size` is in pixels.
+fn nine_slice_quads(
+    rect: Rect,
+    sprite: Sprite,
+    atlas: AtlasId,
+    border: Vec2,
+    atlas_size: Vec2,
+) -> [Quad; 9] {
+    let border = border.min(rect.extents()).max(Vec2::ZERO);
+    let texel_size = 1.0 / atlas_size;
+
+    let sprite_min = sprite.center - sprite.extents;
+    let sprite_max = sprite.center + sprite.extents;
+    let sprite_border = border * PIXELS_PER_UNIT * texel_size;
+
+    let world_edges = [rect.min, rect.min + border, rect.max - border, rect.max];
+    let sprite_edges = [
+        sprite_min,
+        sprite_min + sprite_border,
+        sprite_max - sprite_border,
+        sprite_max,
+    ];
+
+    array::from_fn(|index| {
+        let (x, y) = (index % 3, index / 3);
 
-            self.renderer.dyn_quad_vec.clear();
+        let world_min = vec2(world_edges[x].x, world_edges[y].y);
+        let world_max = vec2(world_edges[x + 1].x, world_edges[y + 1].y);
+        let sprite_min = vec2(sprite_edges[x].x, sprite_edges[y].y);
+        let sprite_max = vec2(sprite_edges[x + 1].x, sprite_edges[y + 1].y);
+
+        let sprite_extents = (sprite_max - sprite_min) / 2.0;
+        let world_extents = (world_max - world_min) / 2.0;
+
+        Quad {
+            center: (world_min + world_max) / 2.0,
+            sprite: Sprite {
+                center: (sprite_min + sprite_max) / 2.0,
+                extents: sprite_extents,
+            },
+            layer: 0.0,
+            atlas,
+            scale: world_extents * PIXELS_PER_UNIT * texel_size / sprite_extents,
+            tint: U8Vec4::MAX,
         }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::UVec2;
+
+    use super::*;
+
+    #[test]
+    fn nine_slice_keeps_corners_at_sprite_size() {
+        let rect = Rect {
+            min: vec2(-4.0, -2.0),
+            max: vec2(4.0, 2.0),
+        };
+        let sprite = Sprite::region(UVec2::ZERO, UVec2::splat(48), UVec2::splat(48));
+
+        let quads = nine_slice_quads(
+            rect,
+            sprite,
+            AtlasId::default(),
+            Vec2::ONE,
+            Vec2::splat(48.0),
+        );
+
+        assert_eq!(quads[0].center, vec2(-3.5, -1.5));
+        assert_eq!(quads[4].center, Vec2::ZERO);
+        assert_eq!(quads[8].center, vec2(3.5, 1.5));
+
+        // Corners keep the sprite's 16 pixel border at its own size.
+        assert_eq!(quads[0].sprite.extents, Vec2::splat(8.0 / 48.0));
+        assert_eq!(quads[0].scale, Vec2::ONE);
     }
 }
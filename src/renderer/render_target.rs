@@ -0,0 +1,90 @@
+use glam::{UVec2, vec2};
+use wgpu::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureUsages, TextureView,
+    TextureViewDescriptor,
+};
+
+use crate::renderer::{AtlasId, RenderContext, Renderer, Sprite};
+
+/// A texture that frames can be rendered into and quads can sample from as an atlas.
+///
+/// A minimap drawn by a second camera, then shown in the corner of the main frame:
+///
+/// ```ignore
+/// let minimap = renderer.create_render_target(uvec2(320, 180), ctx.into());
+///
+/// let mut minimap_camera = Camera::new(player);
+/// minimap_camera.set_zoom(10.0);
+///
+/// renderer.render_frame(
+///     |r| r.render_layer(|r| draw_world(r), minimap_camera),
+///     &minimap.view,
+///     ctx.into(),
+/// );
+///
+/// renderer.render_frame(
+///     |r| {
+///         r.render_layer(|r| draw_world(r), Camera::new(player));
+///         r.render_layer(
+///             |r| {
+///                 let quad = Quad::at(vec2(7.0, 3.5)).sprite(minimap.sprite());
+///                 r.render_quad(quad.atlas(minimap.atlas));
+///             },
+///             Camera::default(),
+///         );
+///     },
+///     output,
+///     ctx.into(),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RenderTarget {
+    pub atlas: AtlasId,
+    pub view: TextureView,
+}
+
+impl Renderer {
+    /// Creates a texture in the surface format, so it can be passed as the output of
+    /// [`Renderer::render_frame`]. Like other atlases, it can also be written with
+    /// [`Renderer::write_atlas_region`].
+    pub fn create_render_target(&mut self, size: UVec2, ctx: RenderContext) -> RenderTarget {
+        let texture = ctx.device.create_texture(&TextureDescriptor {
+            label: Some("renderer render target texture"),
+            size: Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: ctx.surface_format,
+            usage: TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        self.atlases
+            .push(self.create_atlas_bind_group(&texture, ctx));
+        self.atlas_textures.push(texture);
+        self.atlas_sizes.push(vec2(size.x as f32, size.y as f32));
+
+        RenderTarget {
+            atlas: AtlasId(self.atlases.len() as u32 - 1),
+            view,
+        }
+    }
+}
+
+impl RenderTarget {
+    /// The sprite covering the whole target.
+    pub fn sprite(&self) -> Sprite {
+        Sprite {
+            center: vec2(0.5, 0.5),
+            extents: vec2(0.5, 0.5),
+        }
+    }
+}
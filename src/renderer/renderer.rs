@@ -1,41 +1,133 @@
-use std::mem::offset_of;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    mem::offset_of,
+};
 
 use bytemuck::{NoUninit, bytes_of};
-use glam::{Vec2, vec2};
-use image::EncodableLayout;
+use glam::{U8Vec4, UVec2, Vec2, Vec4, vec2};
+use image::{
+    EncodableLayout, ImageError, Rgba, RgbaImage,
+    imageops::{self, FilterType},
+};
 use wgpu::{
-    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
-    BindGroupLayoutEntry, BindingResource, BindingType, BlendState, Buffer, BufferBindingType,
-    BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites, Device, Extent3d, FilterMode,
-    FragmentState, FrontFace, MipmapFilterMode, MultisampleState, Origin3d,
-    PipelineCompilationOptions, PipelineLayoutDescriptor, PolygonMode, PrimitiveState,
-    PrimitiveTopology, Queue, RenderPipeline, RenderPipelineDescriptor, SamplerBindingType,
-    SamplerDescriptor, ShaderStages, TexelCopyBufferLayout, TexelCopyTextureInfo, TextureAspect,
-    TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
-    TextureView, TextureViewDescriptor, TextureViewDimension, VertexAttribute, VertexBufferLayout,
-    VertexFormat, VertexState, VertexStepMode, include_wgsl,
-    util::{BufferInitDescriptor, DeviceExt},
+    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, BlendComponent,
+    BlendFactor, BlendOperation, BlendState, Buffer, BufferBindingType, BufferDescriptor,
+    BufferUsages, COPY_BYTES_PER_ROW_ALIGNMENT, Color, ColorTargetState, ColorWrites,
+    CommandEncoderDescriptor, Device, Extent3d, FilterMode, FragmentState, FrontFace, LoadOp,
+    MapMode, MipmapFilterMode, MultisampleState, Operations, Origin3d, PipelineCompilationOptions,
+    PipelineLayoutDescriptor, PollType, PolygonMode, PrimitiveState, PrimitiveTopology, Queue,
+    RenderPassColorAttachment, RenderPipeline, RenderPipelineDescriptor, Sampler,
+    SamplerBindingType, SamplerDescriptor, ShaderStages, StoreOp, TexelCopyBufferInfo,
+    TexelCopyBufferLayout, TexelCopyTextureInfo, Texture, TextureAspect, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureView,
+    TextureViewDescriptor, TextureViewDimension, VertexAttribute, VertexBufferLayout, VertexFormat,
+    VertexState, VertexStepMode, include_wgsl,
+    util::{BufferInitDescriptor, DeviceExt, StagingBelt},
 };
 
 use crate::{
     asset_path,
     game::GameContext,
-    renderer::{ASPECT, DYN_QUAD_CAP, ORTHO_SIZE, PIXELS_PER_UNIT, RenderBuffer},
+    renderer::{DYN_QUAD_CAP, DebugVertex, MAX_ORTHO_SIZE, PIXELS_PER_UNIT, RenderBuffer},
+    trace_warn,
 };
 
 #[derive(Debug)]
 pub struct Renderer {
     pub(in crate::renderer) vertex_buf: Buffer,
     pub(in crate::renderer) index_buf: Buffer,
+    pub(in crate::renderer) sampler: Sampler,
     pub(in crate::renderer) render_uniform_buf: Buffer,
-    pub(in crate::renderer) render_bind_group: BindGroup,
-    pub(in crate::renderer) render_pipeline: RenderPipeline,
+    pub(in crate::renderer) render_bind_group_layout: BindGroupLayout,
+    pub(in crate::renderer) render_pipelines: [RenderPipeline; 3],
     pub(in crate::renderer) render_texture: TextureView,
+    pub(in crate::renderer) msaa_texture: Option<TextureView>,
     pub(in crate::renderer) upscale_uniform_buf: Buffer,
     pub(in crate::renderer) upscale_bind_group: BindGroup,
     pub(in crate::renderer) upscale_pipeline: RenderPipeline,
     pub(in crate::renderer) dyn_quad_buf: RenderBuffer,
     pub(in crate::renderer) dyn_quad_vec: Vec<Quad>,
+    pub(in crate::renderer) staging_belt: StagingBelt,
+    pub(in crate::renderer) debug_pipeline: RenderPipeline,
+    pub(in crate::renderer) debug_vertex_vec: Vec<DebugVertex>,
+    pub(in crate::renderer) atlases: Vec<BindGroup>,
+    pub(in crate::renderer) atlas_textures: Vec<Texture>,
+    pub(in crate::renderer) atlas_sizes: Vec<Vec2>,
+    pub(in crate::renderer) mipmaps: bool,
+    pub(in crate::renderer) white_atlas: AtlasId,
+    pub(in crate::renderer) letterbox_color: Vec4,
+    pub(in crate::renderer) aspect: f32,
+    pub(in crate::renderer) upscale_mode: UpscaleMode,
+    pub(in crate::renderer) upscale_shader: UpscaleShader,
+    pub(in crate::renderer) brightness: f32,
+    pub(in crate::renderer) contrast: f32,
+    pub(in crate::renderer) gamma: f32,
+    pub(in crate::renderer) fade: Vec4,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RendererConfig {
+    pub aspect: AspectRatio,
+    pub upscale_mode: UpscaleMode,
+    pub filter_mode: FilterMode,
+    pub upscale_shader: UpscaleShader,
+    /// Stores the internal render texture as sRGB instead of linear.
+    ///
+    /// Blending happens in linear space either way. An sRGB render texture avoids the banding
+    /// in dark colors that storing linear values in 8 bits causes, at the cost of frames no
+    /// longer matching the default output bit for bit, so it is off by default.
+    pub srgb_render_texture: bool,
+    /// Samples per pixel of the internal render pass, for smooth edges on rotated and scaled
+    /// quads. 1 disables MSAA, the default that keeps pixel art crisp. Only 1 and 4 are
+    /// supported everywhere, so other counts are rounded to one of them.
+    pub msaa: u32,
+    /// Generates mipmaps for loaded atlases so sprites scaled down stay smooth. Only used with
+    /// linear filtering, and only for atlases whose sides are powers of two.
+    pub mipmaps: bool,
+}
+
+/// The width to height ratio of the internal render texture. The output is letterboxed to it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AspectRatio {
+    #[default]
+    Wide,
+    Standard,
+    Square,
+    Custom(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UpscaleMode {
+    #[default]
+    Fit,
+    IntegerScale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum UpscaleShader {
+    #[default]
+    Plain,
+    /// Scanlines, barrel distortion and a vignette.
+    ///
+    /// `scanline_strength` goes from 0 (none) to 1 (black gaps between lines), `curvature`
+    /// around 0.05 gives a subtle bulge.
+    Crt {
+        scanline_strength: f32,
+        curvature: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    #[default]
+    Alpha,
+    /// Adds to what is behind, for glows and light.
+    Additive,
+    /// Darkens what is behind by the sprite's color, for shadows and tinting. Texels with
+    /// partial alpha come out brighter than a true multiply.
+    Multiply,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -51,6 +143,25 @@ pub struct Quad {
     pub center: Vec2,
     pub sprite: Sprite,
     pub layer: f32,
+    pub atlas: AtlasId,
+    pub scale: Vec2,
+    pub tint: U8Vec4,
+}
+
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, NoUninit)]
+pub struct AtlasId(pub(in crate::renderer) u32);
+
+/// A region passed to [`Renderer::write_atlas_region`] that can't be written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AtlasRegionError {
+    OutOfBounds {
+        min: UVec2,
+        size: UVec2,
+        atlas_size: UVec2,
+    },
+    /// `pixels` is not `size.x * size.y` tightly packed RGBA8 pixels.
+    WrongLength { expected: usize, len: usize },
 }
 
 #[repr(C)]
@@ -64,16 +175,41 @@ pub struct Sprite {
 #[derive(Debug, Clone, Copy, PartialEq, NoUninit)]
 pub(in crate::renderer) struct RenderUniform {
     pub cam_center: Vec2,
+    pub cam_rotation: Vec2,
+    pub cam_ortho_size: f32,
+    pub cam_aspect: f32,
 }
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, NoUninit)]
 pub(in crate::renderer) struct UpscaleUniform {
     pub dst_extents: Vec2,
+    pub dst_offset: Vec2,
+    pub src_extents: Vec2,
+    pub src_offset: Vec2,
+    pub src_height: f32,
+    pub scanline_strength: f32,
+    pub curvature: f32,
+    pub brightness: f32,
+    pub contrast: f32,
+    pub gamma: f32,
+    pub _padding: [f32; 2],
+    pub fade: Vec4,
 }
 
 impl Renderer {
-    pub fn new(ctx: RenderContext) -> Self {
+    /// Creates a renderer whose default atlas is the `sprite_atlas.png` asset.
+    pub fn new(config: &RendererConfig, ctx: RenderContext) -> Result<Self, ImageError> {
+        let sprite_atlas = image::open(asset_path!("sprite_atlas.png"))?.to_rgba8();
+
+        Ok(Self::with_sprite_atlas(config, &sprite_atlas, ctx))
+    }
+
+    pub fn with_sprite_atlas(
+        config: &RendererConfig,
+        sprite_atlas: &RgbaImage,
+        ctx: RenderContext,
+    ) -> Self {
         let vertex_buf = ctx.device.create_buffer_init(&BufferInitDescriptor {
             label: Some("renderer vertex buffer"),
             contents: bytes_of(&[
@@ -91,24 +227,69 @@ impl Renderer {
             usage: BufferUsages::INDEX,
         });
 
+        let render_texture_format = if config.srgb_render_texture {
+            TextureFormat::Rgba8UnormSrgb
+        } else {
+            TextureFormat::Rgba8Unorm
+        };
+
+        let aspect = config.aspect.value();
+        let aspect = if aspect.is_finite() && aspect > 0.0 {
+            aspect
+        } else {
+            trace_warn!("aspect ratio {aspect} is not positive, using 16:9");
+
+            AspectRatio::Wide.value()
+        };
+
         let render_texture = ctx
             .device
             .create_texture(&TextureDescriptor {
                 label: Some("renderer render texture"),
                 dimension: TextureDimension::D2,
-                format: TextureFormat::Rgba8Unorm,
+                format: render_texture_format,
                 mip_level_count: 1,
                 sample_count: 1,
                 size: Extent3d {
-                    width: (PIXELS_PER_UNIT * ORTHO_SIZE * 2.0 * ASPECT) as u32,
-                    height: (PIXELS_PER_UNIT * ORTHO_SIZE * 2.0) as u32,
+                    width: (PIXELS_PER_UNIT * MAX_ORTHO_SIZE * 2.0 * aspect).round() as u32,
+                    height: (PIXELS_PER_UNIT * MAX_ORTHO_SIZE * 2.0) as u32,
                     depth_or_array_layers: 1,
                 },
-                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                usage: TextureUsages::RENDER_ATTACHMENT
+                    | TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_SRC,
                 view_formats: &[],
             })
             .create_view(&TextureViewDescriptor::default());
 
+        let msaa_samples = match config.msaa {
+            0 | 1 => 1,
+            4 => 4,
+            msaa => {
+                let msaa_samples = if msaa < 4 { 1 } else { 4 };
+                trace_warn!("msaa of {msaa} samples is not supported, using {msaa_samples}");
+
+                msaa_samples
+            }
+        };
+
+        let msaa_texture = (msaa_samples > 1).then(|| {
+            let texture = render_texture.texture();
+
+            ctx.device
+                .create_texture(&TextureDescriptor {
+                    label: Some("renderer msaa texture"),
+                    dimension: TextureDimension::D2,
+                    format: texture.format(),
+                    mip_level_count: 1,
+                    sample_count: msaa_samples,
+                    size: texture.size(),
+                    usage: TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+                .create_view(&TextureViewDescriptor::default())
+        });
+
         let render_uniform_buf = ctx.device.create_buffer(&BufferDescriptor {
             label: Some("renderer render uniform buffer"),
             size: size_of::<RenderUniform>() as u64,
@@ -116,44 +297,7 @@ impl Renderer {
             mapped_at_creation: false,
         });
 
-        let sprite_atlas = {
-            let image = image::open(asset_path!("sprite_atlas.png"))
-                .expect("Failed to open renderer sprites texture")
-                .to_rgba8();
-
-            let texture = ctx.device.create_texture(&TextureDescriptor {
-                label: Some("renderer sprites texture"),
-                size: Extent3d {
-                    width: image.width(),
-                    height: image.height(),
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: TextureDimension::D2,
-                format: TextureFormat::Rgba8UnormSrgb,
-                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-                view_formats: &[],
-            });
-
-            ctx.queue.write_texture(
-                TexelCopyTextureInfo {
-                    texture: &texture,
-                    aspect: TextureAspect::All,
-                    mip_level: 0,
-                    origin: Origin3d::ZERO,
-                },
-                image.as_bytes(),
-                TexelCopyBufferLayout {
-                    offset: 0,
-                    bytes_per_row: Some(image.width() * 4),
-                    rows_per_image: Some(image.height()),
-                },
-                texture.size(),
-            );
-
-            texture
-        };
+        let mipmaps = config.mipmaps && config.filter_mode == FilterMode::Linear;
 
         let sampler = ctx.device.create_sampler(&SamplerDescriptor {
             label: Some("renderer sampler"),
@@ -163,13 +307,24 @@ impl Renderer {
             anisotropy_clamp: 1,
             border_color: None,
             compare: None,
-            lod_max_clamp: 1.0,
-            lod_min_clamp: 1.0,
-            mag_filter: FilterMode::Nearest,
-            min_filter: FilterMode::Nearest,
-            mipmap_filter: MipmapFilterMode::Nearest,
+            lod_max_clamp: if mipmaps { 32.0 } else { 1.0 },
+            lod_min_clamp: if mipmaps { 0.0 } else { 1.0 },
+            mag_filter: config.filter_mode,
+            min_filter: config.filter_mode,
+            mipmap_filter: if mipmaps {
+                MipmapFilterMode::Linear
+            } else {
+                MipmapFilterMode::Nearest
+            },
         });
 
+        let filterable = config.filter_mode == FilterMode::Linear;
+        let sampler_binding_type = if filterable {
+            SamplerBindingType::Filtering
+        } else {
+            SamplerBindingType::NonFiltering
+        };
+
         let render_shader = ctx
             .device
             .create_shader_module(include_wgsl!("render.wgsl"));
@@ -187,12 +342,12 @@ impl Renderer {
                                 min_binding_size: None,
                             },
                             count: None,
-                            visibility: ShaderStages::VERTEX,
+                            visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
                         },
                         BindGroupLayoutEntry {
                             binding: 1,
                             ty: BindingType::Texture {
-                                sample_type: TextureSampleType::Float { filterable: false },
+                                sample_type: TextureSampleType::Float { filterable },
                                 view_dimension: TextureViewDimension::D2,
                                 multisampled: false,
                             },
@@ -201,45 +356,79 @@ impl Renderer {
                         },
                         BindGroupLayoutEntry {
                             binding: 2,
-                            ty: BindingType::Sampler(SamplerBindingType::NonFiltering),
+                            ty: BindingType::Sampler(sampler_binding_type),
                             count: None,
                             visibility: ShaderStages::FRAGMENT,
                         },
                     ],
                 });
 
-        let render_bind_group = ctx.device.create_bind_group(&BindGroupDescriptor {
-            label: Some("renderer render bind group"),
-            layout: &render_bind_group_layout,
-            entries: &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: render_uniform_buf.as_entire_binding(),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::TextureView(
-                        &sprite_atlas.create_view(&TextureViewDescriptor::default()),
+        let create_render_pipeline = |blend_mode: BlendMode| {
+            ctx.device
+                .create_render_pipeline(&RenderPipelineDescriptor {
+                    label: Some("renderer render pipeline"),
+                    cache: None,
+                    depth_stencil: None,
+                    layout: Some(
+                        &ctx.device
+                            .create_pipeline_layout(&PipelineLayoutDescriptor {
+                                label: Some("renderer render pipeline layout"),
+                                bind_group_layouts: &[&render_bind_group_layout],
+                                immediate_size: 0,
+                            }),
                     ),
-                },
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::Sampler(&sampler),
-                },
-            ],
-        });
+                    multiview_mask: None,
+                    primitive: PrimitiveState {
+                        front_face: FrontFace::Ccw,
+                        conservative: false,
+                        cull_mode: None,
+                        polygon_mode: PolygonMode::Fill,
+                        strip_index_format: None,
+                        topology: PrimitiveTopology::TriangleList,
+                        unclipped_depth: false,
+                    },
+                    vertex: VertexState {
+                        module: &render_shader,
+                        entry_point: None,
+                        compilation_options: PipelineCompilationOptions::default(),
+                        buffers: &[VERTEX_BUFFER_LAYOUT, QUAD_BUFFER_LAYOUT],
+                    },
+                    fragment: Some(FragmentState {
+                        module: &render_shader,
+                        targets: &[Some(ColorTargetState {
+                            blend: Some(blend_mode.blend_state()),
+                            format: render_texture_format,
+                            write_mask: ColorWrites::all(),
+                        })],
+                        entry_point: None,
+                        compilation_options: PipelineCompilationOptions::default(),
+                    }),
+                    multisample: MultisampleState {
+                        count: msaa_samples,
+                        ..Default::default()
+                    },
+                })
+        };
+
+        let render_pipelines = [
+            create_render_pipeline(BlendMode::Alpha),
+            create_render_pipeline(BlendMode::Additive),
+            create_render_pipeline(BlendMode::Multiply),
+        ];
+
+        let debug_shader = ctx.device.create_shader_module(include_wgsl!("debug.wgsl"));
 
-        let render_pipeline = ctx
+        let debug_pipeline = ctx
             .device
             .create_render_pipeline(&RenderPipelineDescriptor {
-                label: Some("renderer render pipeline"),
+                label: Some("renderer debug pipeline"),
                 cache: None,
                 depth_stencil: None,
                 layout: Some(
                     &ctx.device
                         .create_pipeline_layout(&PipelineLayoutDescriptor {
-                            label: Some("renderer render pipeline layout"),
-                            bind_group_layouts: &[&render_bind_group_layout],
+                            label: Some("renderer debug pipeline layout"),
+                            bind_group_layouts: &[],
                             immediate_size: 0,
                         }),
                 ),
@@ -250,31 +439,37 @@ impl Renderer {
                     cull_mode: None,
                     polygon_mode: PolygonMode::Fill,
                     strip_index_format: None,
-                    topology: PrimitiveTopology::TriangleList,
+                    topology: PrimitiveTopology::LineList,
                     unclipped_depth: false,
                 },
                 vertex: VertexState {
-                    module: &render_shader,
+                    module: &debug_shader,
                     entry_point: None,
                     compilation_options: PipelineCompilationOptions::default(),
-                    buffers: &[VERTEX_BUFFER_LAYOUT, QUAD_BUFFER_LAYOUT],
+                    buffers: &[DEBUG_VERTEX_BUFFER_LAYOUT],
                 },
                 fragment: Some(FragmentState {
-                    module: &render_shader,
+                    module: &debug_shader,
                     targets: &[Some(ColorTargetState {
                         blend: Some(BlendState::ALPHA_BLENDING),
-                        format: TextureFormat::Rgba8Unorm,
+                        format: render_texture_format,
                         write_mask: ColorWrites::all(),
                     })],
                     entry_point: None,
                     compilation_options: PipelineCompilationOptions::default(),
                 }),
-                multisample: MultisampleState::default(),
+                multisample: MultisampleState {
+                    count: msaa_samples,
+                    ..Default::default()
+                },
             });
 
         let upscale_shader = ctx
             .device
-            .create_shader_module(include_wgsl!("upscale.wgsl"));
+            .create_shader_module(match config.upscale_shader {
+                UpscaleShader::Plain => include_wgsl!("upscale.wgsl"),
+                UpscaleShader::Crt { .. } => include_wgsl!("upscale_crt.wgsl"),
+            });
 
         let upscale_uniform_buf = ctx.device.create_buffer(&BufferDescriptor {
             label: Some("renderer upscale uniform buffer"),
@@ -296,12 +491,12 @@ impl Renderer {
                                 min_binding_size: None,
                             },
                             count: None,
-                            visibility: ShaderStages::VERTEX,
+                            visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
                         },
                         BindGroupLayoutEntry {
                             binding: 1,
                             ty: BindingType::Texture {
-                                sample_type: TextureSampleType::Float { filterable: false },
+                                sample_type: TextureSampleType::Float { filterable },
                                 view_dimension: TextureViewDimension::D2,
                                 multisampled: false,
                             },
@@ -310,7 +505,7 @@ impl Renderer {
                         },
                         BindGroupLayoutEntry {
                             binding: 2,
-                            ty: BindingType::Sampler(SamplerBindingType::NonFiltering),
+                            ty: BindingType::Sampler(sampler_binding_type),
                             count: None,
                             visibility: ShaderStages::FRAGMENT,
                         },
@@ -382,22 +577,494 @@ impl Renderer {
         let dyn_quad_buf = RenderBuffer::new_uninit(DYN_QUAD_CAP, ctx);
         let dyn_quad_vec = Vec::with_capacity(DYN_QUAD_CAP);
 
-        Self {
+        let mut result = Self {
             vertex_buf,
             index_buf,
+            sampler,
             render_texture,
+            msaa_texture,
             render_uniform_buf,
-            render_bind_group,
-            render_pipeline,
+            render_bind_group_layout,
+            render_pipelines,
             upscale_uniform_buf,
             upscale_bind_group,
             upscale_pipeline,
             dyn_quad_buf,
             dyn_quad_vec,
+            staging_belt: StagingBelt::new(
+                ctx.device.clone(),
+                (DYN_QUAD_CAP * size_of::<Quad>()) as u64,
+            ),
+            debug_pipeline,
+            debug_vertex_vec: Vec::new(),
+            atlases: Vec::new(),
+            atlas_textures: Vec::new(),
+            atlas_sizes: Vec::new(),
+            mipmaps,
+            white_atlas: AtlasId::default(),
+            letterbox_color: Vec4::W,
+            aspect,
+            upscale_mode: config.upscale_mode,
+            upscale_shader: config.upscale_shader,
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+            fade: Vec4::ZERO,
+        };
+
+        result.load_atlas(sprite_atlas, ctx);
+        result.white_atlas = result.load_atlas(&RgbaImage::from_pixel(1, 1, Rgba([255; 4])), ctx);
+
+        result
+    }
+
+    /// Creates a renderer without a window or assets, for rendering into a texture and reading
+    /// it back with [`Renderer::read_render_texture`]. The default atlas is a single white
+    /// pixel, other atlases can be loaded with [`Renderer::load_atlas`].
+    pub fn new_headless(
+        config: &RendererConfig,
+        device: &Device,
+        queue: &Queue,
+        surface_format: TextureFormat,
+    ) -> Self {
+        Self::with_sprite_atlas(
+            config,
+            &RgbaImage::from_pixel(1, 1, Rgba([255; 4])),
+            RenderContext {
+                device,
+                queue,
+                surface_format,
+            },
+        )
+    }
+
+    pub fn load_atlas(&mut self, image: &RgbaImage, ctx: RenderContext) -> AtlasId {
+        let texture = self.create_atlas_texture(image, ctx);
+        self.atlases
+            .push(self.create_atlas_bind_group(&texture, ctx));
+        self.atlas_textures.push(texture);
+        self.atlas_sizes
+            .push(vec2(image.width() as f32, image.height() as f32));
+
+        AtlasId(self.atlases.len() as u32 - 1)
+    }
+
+    pub fn replace_atlas(&mut self, atlas: AtlasId, image: &RgbaImage, ctx: RenderContext) {
+        let texture = self.create_atlas_texture(image, ctx);
+        self.atlases[atlas.0 as usize] = self.create_atlas_bind_group(&texture, ctx);
+        self.atlas_textures[atlas.0 as usize] = texture;
+        self.atlas_sizes[atlas.0 as usize] = vec2(image.width() as f32, image.height() as f32);
+    }
+
+    /// Overwrites the `size` pixels of an atlas starting at `min`, without recreating its
+    /// texture. `pixels` is tightly packed RGBA8, row by row.
+    ///
+    /// Only the full resolution level is written, so with mipmaps the region keeps its old
+    /// look when drawn scaled down.
+    pub fn write_atlas_region(
+        &self,
+        atlas: AtlasId,
+        min: UVec2,
+        size: UVec2,
+        pixels: &[u8],
+        ctx: RenderContext,
+    ) -> Result<(), AtlasRegionError> {
+        let texture = &self.atlas_textures[atlas.0 as usize];
+        let atlas_size = UVec2::new(texture.width(), texture.height());
+
+        let fits = min
+            .checked_add(size)
+            .is_some_and(|max| max.cmple(atlas_size).all());
+        if !fits {
+            return Err(AtlasRegionError::OutOfBounds {
+                min,
+                size,
+                atlas_size,
+            });
+        }
+
+        let expected = size.x as usize * size.y as usize * 4;
+        if pixels.len() != expected {
+            return Err(AtlasRegionError::WrongLength {
+                expected,
+                len: pixels.len(),
+            });
+        }
+
+        if size.x == 0 || size.y == 0 {
+            return Ok(());
+        }
+
+        ctx.queue.write_texture(
+            TexelCopyTextureInfo {
+                texture,
+                aspect: TextureAspect::All,
+                mip_level: 0,
+                origin: Origin3d {
+                    x: min.x,
+                    y: min.y,
+                    z: 0,
+                },
+            },
+            pixels,
+            TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(size.x * 4),
+                rows_per_image: Some(size.y),
+            },
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Targets the render texture, through the multisampled texture when MSAA is on.
+    pub(in crate::renderer) fn render_texture_attachment(
+        &self,
+        load: LoadOp<Color>,
+    ) -> RenderPassColorAttachment<'_> {
+        let (view, resolve_target) = match &self.msaa_texture {
+            Some(msaa_texture) => (msaa_texture, Some(&self.render_texture)),
+            None => (&self.render_texture, None),
+        };
+
+        RenderPassColorAttachment {
+            view,
+            ops: Operations {
+                load,
+                store: StoreOp::Store,
+            },
+            depth_slice: None,
+            resolve_target,
+        }
+    }
+
+    fn create_atlas_texture(&self, image: &RgbaImage, ctx: RenderContext) -> Texture {
+        let mip_level_count = if !self.mipmaps {
+            1
+        } else if image.width().is_power_of_two() && image.height().is_power_of_two() {
+            image.width().max(image.height()).ilog2() + 1
+        } else {
+            trace_warn!(
+                "atlas of {}x{} pixels is not a power of two, skipping mipmaps",
+                image.width(),
+                image.height(),
+            );
+
+            1
+        };
+
+        let texture = ctx.device.create_texture(&TextureDescriptor {
+            label: Some("renderer atlas texture"),
+            size: Extent3d {
+                width: image.width(),
+                height: image.height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        ctx.queue.write_texture(
+            TexelCopyTextureInfo {
+                texture: &texture,
+                aspect: TextureAspect::All,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+            },
+            image.as_bytes(),
+            TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(image.width() * 4),
+                rows_per_image: Some(image.height()),
+            },
+            texture.size(),
+        );
+
+        for mip_level in 1..mip_level_count {
+            let width = (image.width() >> mip_level).max(1);
+            let height = (image.height() >> mip_level).max(1);
+            let mip = imageops::resize(image, width, height, FilterType::Triangle);
+
+            ctx.queue.write_texture(
+                TexelCopyTextureInfo {
+                    texture: &texture,
+                    aspect: TextureAspect::All,
+                    mip_level,
+                    origin: Origin3d::ZERO,
+                },
+                mip.as_bytes(),
+                TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(width * 4),
+                    rows_per_image: Some(height),
+                },
+                Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        texture
+    }
+
+    pub(in crate::renderer) fn create_atlas_bind_group(
+        &self,
+        texture: &Texture,
+        ctx: RenderContext,
+    ) -> BindGroup {
+        ctx.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("renderer render bind group"),
+            layout: &self.render_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: self.render_uniform_buf.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(
+                        &texture.create_view(&TextureViewDescriptor::default()),
+                    ),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        })
+    }
+
+    /// The width to height ratio of the render texture, for the [`Camera`](crate::renderer::Camera) methods that
+    /// depend on it.
+    pub fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    pub fn set_letterbox_color(&mut self, color: Vec4) {
+        self.letterbox_color = color;
+    }
+
+    /// Added to every color channel when upscaling. 0 leaves the image unchanged.
+    pub fn set_brightness(&mut self, brightness: f32) {
+        self.brightness = brightness;
+    }
+
+    /// Scales every color channel around mid-gray when upscaling. 1 leaves the image unchanged.
+    pub fn set_contrast(&mut self, contrast: f32) {
+        self.contrast = contrast;
+    }
+
+    /// Applied as `color^(1 / gamma)` when upscaling. 1 leaves the image unchanged.
+    pub fn set_gamma(&mut self, gamma: f32) {
+        assert!(gamma > 0.0, "gamma must be positive");
+
+        self.gamma = gamma;
+    }
+
+    /// Blends a color over the whole output, letterbox bars included. An alpha of 0 disables
+    /// the fade and 1 covers the screen completely.
+    pub fn set_fade(&mut self, color: Vec4) {
+        self.fade = color.clamp(Vec4::ZERO, Vec4::ONE);
+    }
+
+    pub fn read_render_texture(&self, ctx: RenderContext) -> RgbaImage {
+        let texture = self.render_texture.texture();
+        let row_len = texture.width() as usize * 4;
+        let padded_row_len = row_len.next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT as usize);
+
+        let readback_buf = ctx.device.create_buffer(&BufferDescriptor {
+            label: Some("renderer readback buffer"),
+            size: (padded_row_len * texture.height() as usize) as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor::default());
+
+        encoder.copy_texture_to_buffer(
+            TexelCopyTextureInfo {
+                texture,
+                aspect: TextureAspect::All,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+            },
+            TexelCopyBufferInfo {
+                buffer: &readback_buf,
+                layout: TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_len as u32),
+                    rows_per_image: Some(texture.height()),
+                },
+            },
+            texture.size(),
+        );
+
+        ctx.queue.submit([encoder.finish()]);
+
+        readback_buf.map_async(MapMode::Read, .., |result| {
+            result.expect("failed to map renderer readback buffer")
+        });
+
+        ctx.device
+            .poll(PollType::wait_indefinitely())
+            .expect("failed to poll device");
+
+        let mut image = RgbaImage::new(texture.width(), texture.height());
+
+        for (src_row, dst_row) in readback_buf
+            .get_mapped_range(..)
+            .chunks(padded_row_len)
+            .zip(image.chunks_mut(row_len))
+        {
+            dst_row.copy_from_slice(&src_row[..row_len]);
+        }
+
+        image
+    }
+}
+
+impl Quad {
+    /// Starts a quad at `center` showing the whole default atlas, untinted and unscaled.
+    ///
+    /// ```ignore
+    /// r.render_quad(Quad::at(pos).sprite(Sprite::region(min, max, atlas_size)).layer(1.0));
+    /// ```
+    pub fn at(center: Vec2) -> Self {
+        Self {
+            center,
+            sprite: Sprite {
+                center: Vec2::splat(0.5),
+                extents: Vec2::splat(0.5),
+            },
+            layer: 0.0,
+            atlas: AtlasId::default(),
+            scale: Vec2::ONE,
+            tint: U8Vec4::MAX,
+        }
+    }
+
+    pub fn sprite(self, sprite: Sprite) -> Self {
+        Self { sprite, ..self }
+    }
+
+    pub fn layer(self, layer: f32) -> Self {
+        Self { layer, ..self }
+    }
+
+    pub fn atlas(self, atlas: AtlasId) -> Self {
+        Self { atlas, ..self }
+    }
+
+    pub fn scale(self, scale: Vec2) -> Self {
+        Self { scale, ..self }
+    }
+
+    pub fn tint(self, color: Vec4) -> Self {
+        Self {
+            tint: color_to_tint(color),
+            ..self
+        }
+    }
+}
+
+impl Sprite {
+    /// The sprite covering the atlas pixels from `min` up to, but not including, `max`.
+    pub fn region(min: UVec2, max: UVec2, atlas_size: UVec2) -> Self {
+        let atlas_size = atlas_size.as_vec2();
+        let min = min.as_vec2();
+        let max = max.as_vec2();
+
+        Self {
+            center: (min + max) / 2.0 / atlas_size,
+            extents: (max - min) / 2.0 / atlas_size,
+        }
+    }
+}
+
+impl AspectRatio {
+    pub fn value(self) -> f32 {
+        match self {
+            Self::Wide => 16.0 / 9.0,
+            Self::Standard => 4.0 / 3.0,
+            Self::Square => 1.0,
+            Self::Custom(aspect) => aspect,
+        }
+    }
+}
+
+impl Display for AtlasRegionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds {
+                min,
+                size,
+                atlas_size,
+            } => write!(
+                f,
+                "atlas region of {}x{} at {min} is outside of the {}x{} atlas",
+                size.x, size.y, atlas_size.x, atlas_size.y
+            ),
+            Self::WrongLength { expected, len } => write!(
+                f,
+                "atlas region needs {expected} bytes of tightly packed RGBA8, got {len}"
+            ),
+        }
+    }
+}
+
+impl Error for AtlasRegionError {}
+
+impl BlendMode {
+    fn blend_state(self) -> BlendState {
+        match self {
+            Self::Alpha => BlendState::ALPHA_BLENDING,
+            Self::Additive => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::Zero,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
+            Self::Multiply => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::Dst,
+                    dst_factor: BlendFactor::OneMinusSrcAlpha,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::Zero,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
         }
     }
 }
 
+pub fn color_to_tint(color: Vec4) -> U8Vec4 {
+    (color.clamp(Vec4::ZERO, Vec4::ONE) * 255.0)
+        .round()
+        .as_u8vec4()
+}
+
 impl<'a> From<GameContext<'a>> for RenderContext<'a> {
     fn from(value: GameContext<'a>) -> Self {
         Self {
@@ -418,6 +1085,23 @@ const VERTEX_BUFFER_LAYOUT: VertexBufferLayout<'static> = VertexBufferLayout {
     }],
 };
 
+const DEBUG_VERTEX_BUFFER_LAYOUT: VertexBufferLayout<'static> = VertexBufferLayout {
+    array_stride: size_of::<DebugVertex>() as u64,
+    step_mode: VertexStepMode::Vertex,
+    attributes: &[
+        VertexAttribute {
+            format: VertexFormat::Float32x2,
+            offset: offset_of!(DebugVertex, pos) as u64,
+            shader_location: 0,
+        },
+        VertexAttribute {
+            format: VertexFormat::Unorm8x4,
+            offset: offset_of!(DebugVertex, color) as u64,
+            shader_location: 1,
+        },
+    ],
+};
+
 const QUAD_BUFFER_LAYOUT: VertexBufferLayout<'static> = VertexBufferLayout {
     array_stride: size_of::<Quad>() as u64,
     step_mode: VertexStepMode::Instance,
@@ -442,5 +1126,213 @@ const QUAD_BUFFER_LAYOUT: VertexBufferLayout<'static> = VertexBufferLayout {
             offset: offset_of!(Quad, layer) as u64,
             shader_location: 4,
         },
+        VertexAttribute {
+            format: VertexFormat::Float32x2,
+            offset: offset_of!(Quad, scale) as u64,
+            shader_location: 5,
+        },
+        VertexAttribute {
+            format: VertexFormat::Unorm8x4,
+            offset: offset_of!(Quad, tint) as u64,
+            shader_location: 6,
+        },
     ],
 };
+
+#[cfg(test)]
+pub(in crate::renderer) mod tests {
+    use std::{env, f32::consts::PI};
+
+    use glam::{UVec2, Vec2, Vec4, uvec2, vec2};
+    use image::RgbaImage;
+    use wgpu::{
+        Device, DeviceDescriptor, Instance, PollType, Queue, RequestAdapterOptions, TextureFormat,
+    };
+
+    use crate::renderer::{
+        AtlasId, AtlasRegionError, Camera, DYN_QUAD_CAP, MAX_ORTHO_SIZE, RenderContext,
+        RenderFrame, Renderer, RendererConfig,
+    };
+
+    const SURFACE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+    const RED: Vec4 = Vec4::new(1.0, 0.0, 0.0, 1.0);
+
+    /// A device for headless tests, preferring the software fallback adapter.
+    ///
+    /// Panics without an adapter, so GPU tests can't pass without running. Setting
+    /// `DRILL_SKIP_GPU_TESTS` returns `None` instead, for machines that have no adapter at all.
+    pub(in crate::renderer) fn device() -> Option<(Device, Queue)> {
+        let instance = Instance::default();
+        let Ok(adapter) = pollster::block_on(instance.request_adapter(&RequestAdapterOptions {
+            force_fallback_adapter: true,
+            ..Default::default()
+        }))
+        .or_else(|_| {
+            pollster::block_on(instance.request_adapter(&RequestAdapterOptions::default()))
+        }) else {
+            if env::var_os("DRILL_SKIP_GPU_TESTS").is_some() {
+                eprintln!("skipping headless render test, no adapter is available");
+                return None;
+            }
+
+            panic!("no adapter for headless render tests, set DRILL_SKIP_GPU_TESTS to skip them");
+        };
+
+        Some(
+            pollster::block_on(adapter.request_device(&DeviceDescriptor::default()))
+                .expect("failed to get device"),
+        )
+    }
+
+    /// Renders a frame with a headless renderer and reads back its render texture.
+    fn render_headless(f: impl FnOnce(&mut RenderFrame)) -> Option<RgbaImage> {
+        let (device, queue) = device()?;
+        let ctx = RenderContext {
+            device: &device,
+            queue: &queue,
+            surface_format: SURFACE_FORMAT,
+        };
+
+        let mut renderer =
+            Renderer::new_headless(&RendererConfig::default(), &device, &queue, SURFACE_FORMAT);
+        let output = renderer.create_render_target(uvec2(64, 64), ctx);
+
+        renderer.render_frame(f, &output.view, ctx);
+
+        Some(renderer.read_render_texture(ctx))
+    }
+
+    fn center_pixel(image: &RgbaImage) -> [u8; 4] {
+        image.get_pixel(image.width() / 2, image.height() / 2).0
+    }
+
+    #[test]
+    fn headless_renders_a_quad() {
+        let Some(image) = render_headless(|r| {
+            r.render_layer(
+                |r| r.draw_rect(Vec2::ZERO, Vec2::ONE, RED),
+                Camera::default(),
+            )
+        }) else {
+            return;
+        };
+
+        assert_eq!(center_pixel(&image), [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn later_flushes_keep_earlier_quads() {
+        let x = 16.0 / 9.0 * MAX_ORTHO_SIZE / 2.0;
+
+        // The first quads fill the dynamic buffer, so the last one is drawn by a second flush.
+        let Some(image) = render_headless(|r| {
+            r.render_layer(
+                |r| {
+                    for _ in 0..DYN_QUAD_CAP {
+                        r.draw_rect(vec2(-x, 0.0), Vec2::ONE, RED);
+                    }
+                    r.draw_rect(vec2(x, 0.0), Vec2::ONE, Vec4::new(0.0, 1.0, 0.0, 1.0));
+                },
+                Camera::default(),
+            )
+        }) else {
+            return;
+        };
+
+        let y = image.height() / 2;
+        assert_eq!(image.get_pixel(image.width() / 4, y).0, [255, 0, 0, 255]);
+        assert_eq!(
+            image.get_pixel(image.width() * 3 / 4, y).0,
+            [0, 255, 0, 255]
+        );
+    }
+
+    #[test]
+    fn quad_at_camera_center_stays_centered_when_rotated() {
+        for rotation in [0.3, 1.0, PI / 2.0, 2.5, -PI] {
+            let camera = Camera {
+                rotation,
+                ..Camera::new(vec2(3.25, -1.75))
+            };
+
+            let Some(image) = render_headless(|r| {
+                r.render_layer(
+                    |r| r.draw_rect(camera.center, Vec2::splat(0.25), RED),
+                    camera,
+                )
+            }) else {
+                return;
+            };
+
+            assert_eq!(
+                center_pixel(&image),
+                [255, 0, 0, 255],
+                "rotation {rotation}"
+            );
+        }
+    }
+
+    #[test]
+    fn write_atlas_region_rejects_bad_regions() {
+        let Some((device, queue)) = device() else {
+            return;
+        };
+        let ctx = RenderContext {
+            device: &device,
+            queue: &queue,
+            surface_format: SURFACE_FORMAT,
+        };
+
+        // The headless default atlas is a single pixel.
+        let renderer =
+            Renderer::new_headless(&RendererConfig::default(), &device, &queue, SURFACE_FORMAT);
+        let atlas = AtlasId::default();
+
+        assert_eq!(
+            renderer.write_atlas_region(atlas, UVec2::ZERO, UVec2::ONE, &[0; 4], ctx),
+            Ok(())
+        );
+        assert_eq!(
+            renderer.write_atlas_region(atlas, UVec2::ONE, UVec2::ONE, &[0; 4], ctx),
+            Err(AtlasRegionError::OutOfBounds {
+                min: UVec2::ONE,
+                size: UVec2::ONE,
+                atlas_size: UVec2::ONE,
+            })
+        );
+        assert!(matches!(
+            renderer.write_atlas_region(atlas, UVec2::MAX, UVec2::ONE, &[0; 4], ctx),
+            Err(AtlasRegionError::OutOfBounds { .. })
+        ));
+        assert_eq!(
+            renderer.write_atlas_region(atlas, UVec2::ZERO, UVec2::ONE, &[0; 3], ctx),
+            Err(AtlasRegionError::WrongLength {
+                expected: 4,
+                len: 3
+            })
+        );
+    }
+
+    #[test]
+    fn write_atlas_region_accepts_render_targets() {
+        let Some((device, queue)) = device() else {
+            return;
+        };
+        let ctx = RenderContext {
+            device: &device,
+            queue: &queue,
+            surface_format: SURFACE_FORMAT,
+        };
+
+        let mut renderer =
+            Renderer::new_headless(&RendererConfig::default(), &device, &queue, SURFACE_FORMAT);
+        let target = renderer.create_render_target(uvec2(4, 4), ctx);
+
+        assert_eq!(
+            renderer.write_atlas_region(target.atlas, uvec2(2, 2), UVec2::splat(2), &[0; 16], ctx),
+            Ok(())
+        );
+        device.poll(PollType::wait_indefinitely()).unwrap();
+    }
+}
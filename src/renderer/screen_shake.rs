@@ -0,0 +1,86 @@
+use glam::{Vec2, vec2};
+
+use crate::game::Rng;
+
+/// Shakes the camera by an amount that builds up with `add_trauma` and wears off over time.
+///
+/// ```ignore
+/// camera.center += shake.offset(dt);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenShake {
+    pub trauma: f32,
+    /// Trauma lost per second.
+    pub decay: f32,
+    /// Offset at full trauma, in world units.
+    pub max_offset: Vec2,
+    rng: Rng,
+}
+
+impl ScreenShake {
+    pub fn new() -> Self {
+        Self::with_seed(0x2545_f491)
+    }
+
+    /// Shakes the same way every run for the same seed and calls, for replays.
+    pub fn with_seed(seed: u32) -> Self {
+        Self {
+            trauma: 0.0,
+            decay: 1.0,
+            max_offset: Vec2::splat(0.5),
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn offset(&mut self, dt: f32) -> Vec2 {
+        let shake = self.trauma * self.trauma;
+        self.trauma = (self.trauma - self.decay * dt).max(0.0);
+
+        if shake == 0.0 {
+            return Vec2::ZERO;
+        }
+
+        let direction = vec2(self.rng.range(-1.0..1.0), self.rng.range(-1.0..1.0));
+
+        direction * self.max_offset * shake
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(seed: u32) -> Vec<Vec2> {
+        let mut shake = ScreenShake::with_seed(seed);
+        shake.add_trauma(0.8);
+
+        (0..10).map(|_| shake.offset(1.0 / 60.0)).collect()
+    }
+
+    #[test]
+    fn same_seed_shakes_the_same() {
+        assert_eq!(offsets(7), offsets(7));
+        assert_ne!(offsets(7), offsets(8));
+    }
+
+    #[test]
+    fn shake_scales_with_trauma_squared_and_wears_off() {
+        let mut shake = ScreenShake::with_seed(7);
+        shake.add_trauma(0.5);
+
+        let offset = shake.offset(0.25);
+        assert!(
+            offset.abs().cmple(shake.max_offset * 0.25).all(),
+            "{offset}"
+        );
+        assert_eq!(shake.trauma, 0.25);
+
+        shake.offset(1.0);
+        assert_eq!(shake.trauma, 0.0);
+        assert_eq!(shake.offset(1.0), Vec2::ZERO);
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use crate::renderer::Sprite;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteAnimation {
+    pub frames: Vec<Sprite>,
+    pub fps: f32,
+    pub looping: bool,
+}
+
+impl SpriteAnimation {
+    pub fn sample(&self, elapsed: Duration) -> Sprite {
+        assert!(!self.frames.is_empty());
+
+        let frame_index = (elapsed.as_secs_f32() * self.fps) as usize;
+
+        let frame_index = if self.looping {
+            frame_index % self.frames.len()
+        } else {
+            frame_index.min(self.frames.len() - 1)
+        };
+
+        self.frames[frame_index]
+    }
+
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        !self.looping && elapsed.as_secs_f32() * self.fps >= self.frames.len() as f32
+    }
+}
@@ -0,0 +1,130 @@
+use glam::{U8Vec4, UVec2, Vec2, uvec2};
+
+use crate::renderer::{AtlasId, Quad, RenderBuffer, RenderLayer, Sprite};
+
+#[derive(Debug)]
+pub struct Tilemap {
+    size: UVec2,
+    tile_size: Vec2,
+    origin: Vec2,
+    atlas: AtlasId,
+    tiles: Vec<u32>,
+    buffer: Option<RenderBuffer>,
+}
+
+impl Tilemap {
+    pub fn new(size: UVec2, tile_size: Vec2, origin: Vec2, atlas: AtlasId) -> Self {
+        Self {
+            size,
+            tile_size,
+            origin,
+            atlas,
+            tiles: vec![0; (size.x * size.y) as usize],
+            buffer: None,
+        }
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    pub fn get(&self, pos: UVec2) -> u32 {
+        self.tiles[self.index(pos)]
+    }
+
+    pub fn set(&mut self, pos: UVec2, tile: u32) {
+        let index = self.index(pos);
+
+        if self.tiles[index] != tile {
+            self.tiles[index] = tile;
+            self.buffer = None;
+        }
+    }
+
+    pub fn set_origin(&mut self, origin: Vec2) {
+        if self.origin != origin {
+            self.origin = origin;
+            self.buffer = None;
+        }
+    }
+
+    pub fn tile_center(&self, pos: UVec2) -> Vec2 {
+        self.origin + (pos.as_vec2() + 0.5) * self.tile_size
+    }
+
+    pub fn build_quads(&self, lookup: impl Fn(u32) -> Option<Sprite>) -> Vec<Quad> {
+        let mut quads = Vec::new();
+
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                if let Some(quad) = self.tile_quad(uvec2(x, y), &lookup) {
+                    quads.push(quad);
+                }
+            }
+        }
+
+        quads
+    }
+
+    fn tile_quad(&self, pos: UVec2, lookup: impl Fn(u32) -> Option<Sprite>) -> Option<Quad> {
+        Some(Quad {
+            center: self.tile_center(pos),
+            sprite: lookup(self.get(pos))?,
+            layer: 0.0,
+            atlas: self.atlas,
+            scale: Vec2::ONE,
+            tint: U8Vec4::MAX,
+        })
+    }
+
+    fn index(&self, pos: UVec2) -> usize {
+        assert!(pos.x < self.size.x && pos.y < self.size.y);
+
+        (pos.y * self.size.x + pos.x) as usize
+    }
+}
+
+impl<'a> RenderLayer<'a> {
+    pub fn render_tilemap(
+        &mut self,
+        tilemap: &mut Tilemap,
+        lookup: impl Fn(u32) -> Option<Sprite>,
+    ) {
+        if tilemap.buffer.is_none() {
+            let quads = tilemap.build_quads(lookup);
+            tilemap.buffer = Some(RenderBuffer::new(&quads, *self.ctx));
+        }
+
+        if let Some(buffer) = &tilemap.buffer
+            && buffer.len() > 0
+        {
+            self.render_buffer(buffer.slice(..), tilemap.atlas);
+        }
+    }
+
+    pub fn render_visible_tiles(
+        &mut self,
+        tilemap: &Tilemap,
+        lookup: impl Fn(u32) -> Option<Sprite>,
+    ) {
+        let visible_rect = self.visible_rect();
+
+        let min = ((visible_rect.min - tilemap.origin) / tilemap.tile_size)
+            .floor()
+            .max(Vec2::ZERO)
+            .as_uvec2();
+        let max = ((visible_rect.max - tilemap.origin) / tilemap.tile_size)
+            .ceil()
+            .max(Vec2::ZERO)
+            .as_uvec2()
+            .min(tilemap.size);
+
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                if let Some(quad) = tilemap.tile_quad(uvec2(x, y), &lookup) {
+                    self.render_quad(quad);
+                }
+            }
+        }
+    }
+}
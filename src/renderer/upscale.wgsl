@@ -9,6 +9,22 @@ struct Fragment {
 
 struct Uniform {
     dst_extents: vec2f,
+    dst_offset: vec2f,
+    src_extents: vec2f,
+    src_offset: vec2f,
+    src_height: f32,
+    scanline_strength: f32,
+    curvature: f32,
+    brightness: f32,
+    contrast: f32,
+    gamma: f32,
+    fade: vec4f,
+}
+
+fn adjust_color(color: vec3f) -> vec3f {
+    let adjusted = (color - 0.5) * u.contrast + 0.5 + u.brightness;
+
+    return pow(max(adjusted, vec3f(0.0)), vec3f(1.0 / u.gamma));
 }
 
 @group(0) @binding(0) var<uniform> u: Uniform;
@@ -19,13 +35,15 @@ struct Uniform {
 fn vs_main(input: Vertex) -> Fragment {
     var output: Fragment;
 
-    output.pos = vec4f(input.vertex_pos * u.dst_extents, 0.0, 1.0);
-    output.uv = input.vertex_pos * vec2f(0.5, -0.5) + 0.5;
+    output.pos = vec4f(input.vertex_pos * u.dst_extents + u.dst_offset, 0.0, 1.0);
+    output.uv = input.vertex_pos * vec2f(1.0, -1.0) * u.src_extents + 0.5 + u.src_offset;
     
     return output;
 }
 
 @fragment
 fn fs_main(input: Fragment) -> @location(0) vec4f {
-    return textureSample(render_texture, render_texture_sampler, input.uv);
+    let color = textureSample(render_texture, render_texture_sampler, input.uv);
+
+    return vec4f(adjust_color(color.rgb) * (1.0 - u.fade.a) + u.fade.rgb, color.a);
 }
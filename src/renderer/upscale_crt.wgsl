@@ -0,0 +1,63 @@
+struct Vertex {
+    @location(0) vertex_pos: vec2f,
+}
+
+struct Fragment {
+    @builtin(position) pos: vec4f,
+    @location(0) local_pos: vec2f,
+}
+
+struct Uniform {
+    dst_extents: vec2f,
+    dst_offset: vec2f,
+    src_extents: vec2f,
+    src_offset: vec2f,
+    src_height: f32,
+    scanline_strength: f32,
+    curvature: f32,
+    brightness: f32,
+    contrast: f32,
+    gamma: f32,
+    fade: vec4f,
+}
+
+const PI: f32 = 3.14159265;
+
+fn adjust_color(color: vec3f) -> vec3f {
+    let adjusted = (color - 0.5) * u.contrast + 0.5 + u.brightness;
+
+    return pow(max(adjusted, vec3f(0.0)), vec3f(1.0 / u.gamma));
+}
+
+@group(0) @binding(0) var<uniform> u: Uniform;
+@group(0) @binding(1) var render_texture: texture_2d<f32>;
+@group(0) @binding(2) var render_texture_sampler: sampler;
+
+@vertex
+fn vs_main(input: Vertex) -> Fragment {
+    var output: Fragment;
+
+    output.pos = vec4f(input.vertex_pos * u.dst_extents + u.dst_offset, 0.0, 1.0);
+    output.local_pos = input.vertex_pos;
+    
+    return output;
+}
+
+@fragment
+fn fs_main(input: Fragment) -> @location(0) vec4f {
+    let curved_pos = input.local_pos * (1.0 + u.curvature * input.local_pos.yx * input.local_pos.yx);
+    let uv = curved_pos * vec2f(1.0, -1.0) * u.src_extents + 0.5 + u.src_offset;
+
+    let color = textureSample(render_texture, render_texture_sampler, uv);
+
+    if any(abs(curved_pos) > vec2f(1.0)) {
+        return vec4f(u.fade.rgb, 1.0);
+    }
+
+    let scanline = mix(1.0, 0.5 + 0.5 * cos(uv.y * u.src_height * 2.0 * PI), u.scanline_strength);
+    let vignette = 1.0 - 0.25 * dot(curved_pos * curved_pos, curved_pos * curved_pos);
+
+    let crt_color = adjust_color(color.rgb) * scanline * vignette;
+
+    return vec4f(crt_color * (1.0 - u.fade.a) + u.fade.rgb, color.a);
+}
@@ -0,0 +1,44 @@
+/// Enters a `tracing` span until the end of the enclosing block when the `trace` feature is
+/// enabled, and expands to nothing otherwise.
+#[macro_export]
+macro_rules! trace_span {
+    ($name:literal) => {
+        #[cfg(feature = "trace")]
+        let _span = tracing::info_span!($name).entered();
+    };
+}
+
+/// Prints a warning to stderr. With the `trace` feature it is logged through `tracing`
+/// instead, unless no subscriber is installed to show it.
+#[macro_export]
+macro_rules! trace_warn {
+    ($($arg:tt)*) => {
+        $crate::trace_log!(warn, "warning", $($arg)*)
+    };
+}
+
+/// Prints an error to stderr, or logs it through `tracing` like [`trace_warn!`].
+#[macro_export]
+macro_rules! trace_error {
+    ($($arg:tt)*) => {
+        $crate::trace_log!(error, "error", $($arg)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trace_log {
+    ($level:ident, $prefix:literal, $($arg:tt)*) => {{
+        #[cfg(feature = "trace")]
+        let is_traced = tracing::dispatcher::has_been_set();
+        #[cfg(not(feature = "trace"))]
+        let is_traced = false;
+
+        if is_traced {
+            #[cfg(feature = "trace")]
+            tracing::$level!($($arg)*);
+        } else {
+            eprintln!("{}: {}", $prefix, format_args!($($arg)*));
+        }
+    }};
+}